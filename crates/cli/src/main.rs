@@ -0,0 +1,212 @@
+use std::{fs::File, io::BufReader, path::PathBuf, process::ExitCode};
+
+use clap::{Args, Parser, Subcommand};
+use feta::{Context, ContextBuilder, Features, FetaError, Object};
+use serde::Serialize;
+
+/// Evaluates feta features offline from a JSON configuration file.
+#[derive(Debug, Parser)]
+#[command(name = "feta", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Evaluates a single feature and prints the decision as JSON.
+    Decide {
+        #[command(flatten)]
+        args: ContextArgs,
+        /// The name of the feature to evaluate.
+        #[arg(long)]
+        feature: String,
+    },
+    /// Evaluates all features and prints the decisions as JSON, keyed by feature name.
+    DecideAll {
+        #[command(flatten)]
+        args: ContextArgs,
+    },
+}
+
+/// The arguments used to load the features and build the evaluation context.
+#[derive(Debug, Args)]
+struct ContextArgs {
+    /// The path to the JSON configuration file.
+    #[arg(long)]
+    config: PathBuf,
+    /// The user key used for targeting and bucketing.
+    #[arg(
+        long,
+        required_unless_present = "anonymous",
+        conflicts_with = "anonymous"
+    )]
+    user: Option<String>,
+    /// Evaluates with an ephemeral bucketing key in place of a user key.
+    #[arg(long)]
+    anonymous: bool,
+    /// A context attribute as key=value, where the value is parsed as JSON or treated as a string.
+    #[arg(long = "attr", value_name = "KEY=VALUE", value_parser = parse_attribute)]
+    attributes: Vec<(String, Object)>,
+}
+
+impl ContextArgs {
+    /// Loads the features from the configuration file.
+    fn features(&self) -> Result<Features, FetaError> {
+        let file = File::open(&self.config).map_err(|e| {
+            FetaError::Configuration(format!("failed to read {}: {}", self.config.display(), e))
+        })?;
+
+        Features::from_reader(BufReader::new(file))
+    }
+
+    /// Builds the evaluation context from the user key and attributes.
+    fn context(&self) -> Result<Context, FetaError> {
+        let mut builder = match &self.user {
+            Some(user) => ContextBuilder::new().user_key(user),
+            None => ContextBuilder::new().anonymous(),
+        };
+
+        for (key, value) in &self.attributes {
+            builder = builder.attribute(key, value.clone());
+        }
+
+        builder.build()
+    }
+}
+
+/// Parses a key=value attribute, treating values that are not valid JSON as strings.
+fn parse_attribute(s: &str) -> Result<(String, Object), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid attribute '{}': expected KEY=VALUE", s))?;
+
+    if key.is_empty() {
+        return Err(format!("invalid attribute '{}': key is required", s));
+    }
+
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Object::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Runs the command, returning the output as pretty-printed JSON.
+fn run(command: &Command) -> Result<String, FetaError> {
+    match command {
+        Command::Decide { args, feature } => {
+            let decision = args.features()?.decide(feature, &args.context()?);
+            to_json(&decision)
+        }
+        Command::DecideAll { args } => {
+            let decisions = args.features()?.decide_all_sorted(&args.context()?);
+            to_json(&decisions)
+        }
+    }
+}
+
+/// Serializes the value as pretty-printed JSON.
+fn to_json(value: &impl Serialize) -> Result<String, FetaError> {
+    serde_json::to_string_pretty(value).map_err(|e| FetaError::Request(e.to_string()))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(&cli.command) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_attribute() {
+        let tests = vec![
+            ("beta=true", Ok(("beta", Object::Boolean(true)))),
+            ("age=30", Ok(("age", Object::Integer(30)))),
+            ("score=1.5", Ok(("score", Object::Float(1.5)))),
+            (
+                "email=a@b.com",
+                Ok(("email", Object::String("a@b.com".to_string()))),
+            ),
+            (
+                "name=\"30\"",
+                Ok(("name", Object::String("30".to_string()))),
+            ),
+            ("empty=", Ok(("empty", Object::String(String::new())))),
+            ("beta", Err(())),
+            ("=true", Err(())),
+        ];
+
+        for (input, expected) in tests {
+            let actual = parse_attribute(input).map_err(|_| ());
+            let expected = expected.map(|(k, v)| (k.to_string(), v));
+            assert_eq!(actual, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let config = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../integration/tests/config.json"
+        );
+
+        let cli = Cli::try_parse_from([
+            "feta",
+            "decide",
+            "--config",
+            config,
+            "--feature",
+            "bool_feature",
+            "--user",
+            "g",
+        ])
+        .expect("args should parse");
+
+        let actual: serde_json::Value =
+            serde_json::from_str(&run(&cli.command).expect("decide should succeed")).unwrap();
+        assert_eq!(actual["variant"], "on");
+        assert_eq!(actual["value"], true);
+
+        let cli = Cli::try_parse_from([
+            "feta",
+            "decide-all",
+            "--config",
+            config,
+            "--anonymous",
+            "--attr",
+            "is_beta=true",
+        ])
+        .expect("args should parse");
+
+        let actual: serde_json::Value =
+            serde_json::from_str(&run(&cli.command).expect("decide-all should succeed")).unwrap();
+        assert_eq!(actual["bool_feature"]["variant"], "on");
+
+        let cli = Cli::try_parse_from([
+            "feta",
+            "decide",
+            "--config",
+            "invalid.json",
+            "--feature",
+            "f1",
+            "--user",
+            "g",
+        ])
+        .expect("args should parse");
+
+        let err = run(&cli.command).expect_err("missing config should fail");
+        assert!(matches!(err, FetaError::Configuration(_)));
+
+        assert!(Cli::try_parse_from(["feta", "decide-all", "--config", config]).is_err());
+    }
+}
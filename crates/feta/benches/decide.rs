@@ -0,0 +1,50 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use feta::{Context, ContextBuilder, Features};
+
+/// Creates a `Features` instance with a single feature that has an audience rule, a prerequisite and a default split.
+fn new_features() -> Features {
+    Features::from_json(
+        r#"{
+            "features": {
+                "parent": {
+                    "enabled": true,
+                    "value_type": "boolean",
+                    "variants": { "off": false, "on": true },
+                    "default_variant": "off",
+                    "default_rule": { "variant": "on" }
+                },
+                "child": {
+                    "enabled": true,
+                    "value_type": "integer",
+                    "variants": { "a": 1, "b": 2 },
+                    "default_variant": "a",
+                    "prerequisites": [{ "feature": "parent", "variant": "on" }],
+                    "audience_rules": [
+                        { "name": "beta", "expression": "is_beta", "variant": "b" }
+                    ],
+                    "default_rule": { "distribution": { "a": 50, "b": 50 } }
+                }
+            }
+        }"#,
+    )
+    .expect("features should build")
+}
+
+fn bench_decide(c: &mut Criterion) {
+    let features = new_features();
+    let ctx = Context::new("user");
+    let beta = ContextBuilder::new()
+        .user_key("user")
+        .bool_attribute("is_beta", true)
+        .build()
+        .expect("context should build");
+
+    let mut group = c.benchmark_group("decide");
+    group.bench_function("default", |b| b.iter(|| features.decide("child", &ctx)));
+    group.bench_function("audience", |b| b.iter(|| features.decide("child", &beta)));
+    group.bench_function("not_found", |b| b.iter(|| features.decide("invalid", &ctx)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_decide);
+criterion_main!(benches);
@@ -0,0 +1,49 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use feta::{Context, Features, config::Config};
+
+/// Creates a `Features` instance with the specified number of features, each with an audience rule and a default split.
+fn new_features(count: usize) -> Features {
+    let features: Vec<String> = (0..count)
+        .map(|i| {
+            format!(
+                r#""f{i}": {{
+                    "enabled": true,
+                    "value_type": "integer",
+                    "variants": {{ "a": 1, "b": 2 }},
+                    "default_variant": "a",
+                    "audience_rules": [
+                        {{ "name": "beta", "expression": "is_beta", "variant": "b" }}
+                    ],
+                    "default_rule": {{ "distribution": {{ "a": 50, "b": 50 }} }}
+                }}"#
+            )
+        })
+        .collect();
+
+    let json = format!(r#"{{"features": {{ {} }}}}"#, features.join(","));
+    let config: Config = serde_json::from_str(&json).expect("config should deserialize");
+    Features::from_config(&config).expect("features should build")
+}
+
+fn bench_decide_all(c: &mut Criterion) {
+    let ctx = Context::new("user");
+    let mut group = c.benchmark_group("decide_all");
+
+    for count in [10, 100, 250, 500, 1000] {
+        let features = new_features(count);
+
+        group.bench_with_input(BenchmarkId::new("serial", count), &features, |b, f| {
+            b.iter(|| f.decide_all(&ctx))
+        });
+
+        #[cfg(feature = "rayon")]
+        group.bench_with_input(BenchmarkId::new("parallel", count), &features, |b, f| {
+            b.iter(|| f.par_decide_all(&ctx))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_decide_all);
+criterion_main!(benches);
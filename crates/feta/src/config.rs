@@ -1,46 +1,794 @@
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{borrow::Cow, collections::BTreeMap, io::Read};
 
-use crate::value::{Value, ValueType};
+use crate::{
+    error::FetaError,
+    features,
+    hash::{HashAlgorithm, HashKeyFormat},
+    value::{Value, ValueType},
+};
+
+/// The latest configuration schema version, which is the only version currently supported.
+pub const SCHEMA_VERSION: u32 = 1;
 
 /// The configuration for all features.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Config {
+    /// The schema version the configuration was written for, which defaults to 1 for configs that predate versioning.
+    #[serde(default = "default_version")]
+    pub version: u32,
+    /// The murmur3 seed used for bucketing, which can be changed to decorrelate deployments that share user keys.
+    #[serde(default)]
+    pub seed: u32,
+    /// The algorithm used to hash user keys into buckets, which defaults to Murmur3.
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    /// How feature and user keys are combined before hashing, which defaults to concatenation to preserve buckets.
+    #[serde(default)]
+    pub hash_key_format: HashKeyFormat,
     pub features: BTreeMap<String, Feature>,
+    /// The mutually exclusive groups of features, keyed by group name, where each user is assigned to at most one feature per group.
+    #[serde(default)]
+    pub exclusion_groups: BTreeMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Parses the configuration from a JSON string.
+    pub fn from_json(json: &str) -> Result<Config, FetaError> {
+        serde_json::from_str(json).map_err(|e| FetaError::Configuration(e.to_string()))
+    }
+
+    /// Parses the configuration from a JSON string, rejecting unknown fields such as a misspelled `default_rul`.
+    ///
+    /// Unknown fields are found by comparing the input with the parsed configuration, so `from_json` remains lenient
+    /// for forward compatibility.
+    pub fn from_json_strict(json: &str) -> Result<Config, FetaError> {
+        let input: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| FetaError::Configuration(e.to_string()))?;
+        let cfg: Config = serde_json::from_value(input.clone())
+            .map_err(|e| FetaError::Configuration(e.to_string()))?;
+        let parsed =
+            serde_json::to_value(&cfg).map_err(|e| FetaError::Configuration(e.to_string()))?;
+
+        match find_unknown_field(&input, &parsed, "") {
+            Some(path) => Err(FetaError::Configuration(format!("unknown field: {}", path))),
+            None => Ok(cfg),
+        }
+    }
+
+    /// Parses the configuration from a reader that produces JSON.
+    pub fn from_reader(reader: impl Read) -> Result<Config, FetaError> {
+        serde_json::from_reader(reader).map_err(|e| FetaError::Configuration(e.to_string()))
+    }
+
+    /// Parses the configuration from a YAML string, using the same shape as the JSON configuration.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Config, FetaError> {
+        serde_yaml::from_str(yaml).map_err(|e| FetaError::Configuration(e.to_string()))
+    }
+
+    /// Parses the configuration from a TOML string, using the same shape as the JSON configuration.
+    ///
+    /// Each feature is a `[features.<name>]` table. Rules bucket with either a `variant` key or a
+    /// `distribution` table, and audience rules are an `[[features.<name>.audience_rules]]` array of tables:
+    ///
+    /// ```toml
+    /// [features.my_feature]
+    /// enabled = true
+    /// value_type = "boolean"
+    /// variants = { off = false, on = true }
+    /// default_variant = "off"
+    /// default_rule = { distribution = { off = 90, on = 10 } }
+    ///
+    /// [[features.my_feature.audience_rules]]
+    /// name = "beta"
+    /// expression = "is_beta"
+    /// variant = "on"
+    /// ```
+    ///
+    /// TOML has no null value, so `null` variant values cannot be expressed.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Config, FetaError> {
+        toml::from_str(toml).map_err(|e| FetaError::Configuration(e.to_string()))
+    }
+
+    /// Validates the configuration, returning all errors rather than failing on the first.
+    pub fn validate(&self) -> Result<(), Vec<FetaError>> {
+        self.check_version().map_err(|e| vec![e])?;
+        let cfg = self.interpolate(&env_var).map_err(|e| vec![e])?;
+
+        let errors = features::config_errors(&cfg);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Applies the overlay to the configuration, such as a per-environment overlay on a base configuration.
+    ///
+    /// Features are merged by key, and features that only exist in the overlay are added, in which case they must
+    /// specify `enabled` and `value_type`. For features that exist in both:
+    ///
+    /// - `variants` and `variant_metadata` are merged by variant key, and `dimension_rules` by dimension value, with
+    ///   overlay values taking precedence
+    /// - `audience_rules` are merged by rule name, with overlay rules replacing existing rules in place and new rules
+    ///   appended in order
+    /// - all other fields are replaced if they are specified in the overlay, including `tags` and `prerequisites`,
+    ///   which are replaced as a whole
+    ///
+    /// The `seed`, `hash_algorithm` and `hash_key_format` are replaced if specified, and `exclusion_groups` are merged by group name.
+    /// Fields cannot be removed by an overlay, and the merged configuration is validated when features are built.
+    pub fn merge(&mut self, overlay: Overlay) -> Result<(), FetaError> {
+        if let Some(seed) = overlay.seed {
+            self.seed = seed;
+        }
+
+        if let Some(hash_algorithm) = overlay.hash_algorithm {
+            self.hash_algorithm = hash_algorithm;
+        }
+
+        if let Some(hash_key_format) = overlay.hash_key_format {
+            self.hash_key_format = hash_key_format;
+        }
+
+        for (name, feature) in overlay.features {
+            match self.features.get_mut(&name) {
+                Some(existing) => existing.merge(feature),
+                None => {
+                    let feature = feature.into_feature().map_err(|field| {
+                        FetaError::Configuration(format!(
+                            "overlay feature {} is missing required field: {}",
+                            name, field
+                        ))
+                    })?;
+                    self.features.insert(name, feature);
+                }
+            }
+        }
+
+        self.exclusion_groups.extend(overlay.exclusion_groups);
+        Ok(())
+    }
+
+    /// Returns an error if the configuration was written for an unsupported schema version.
+    pub(crate) fn check_version(&self) -> Result<(), FetaError> {
+        if self.version == SCHEMA_VERSION {
+            Ok(())
+        } else {
+            Err(FetaError::Configuration(format!(
+                "unsupported config version: {} (supported: {})",
+                self.version, SCHEMA_VERSION
+            )))
+        }
+    }
+
+    /// Replaces `${NAME}` variables in audience expressions with values from the lookup function.
+    ///
+    /// The configuration is only cloned if it contains variables, and undefined variables are an error.
+    pub(crate) fn interpolate(
+        &self,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Cow<'_, Config>, FetaError> {
+        let has_variables = self
+            .features
+            .values()
+            .flat_map(|f| &f.audience_rules)
+            .any(|r| r.expression.sources().any(|s| s.contains("${")));
+
+        if !has_variables {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let mut cfg = self.clone();
+        for (name, feature) in cfg.features.iter_mut() {
+            for rule in feature.audience_rules.iter_mut() {
+                rule.expression.interpolate(lookup).map_err(|e| {
+                    e.with_context(&format!("in feature '{}' audience '{}'", name, rule.name))
+                })?;
+            }
+        }
+
+        Ok(Cow::Owned(cfg))
+    }
 }
 
 /// The configuration for a single feature.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Feature {
     pub enabled: bool,
+    /// The tags used to group features, such as by owning team.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The salt mixed into the hash to decorrelate bucketing from other features.
+    #[serde(default)]
+    pub salt: String,
+    /// The context attribute used for bucketing in place of the user key.
+    pub bucketing_key: Option<String>,
     pub value_type: ValueType,
+    pub element_type: Option<ValueType>,
+    /// The variants for the feature, which can be omitted for boolean features to use `on` and `off` variants.
+    #[serde(default)]
     pub variants: BTreeMap<String, Value>,
+    /// The metadata for each variant, such as a display name or analytics ID, which is returned with its decisions.
+    #[serde(default)]
+    pub variant_metadata: BTreeMap<String, BTreeMap<String, String>>,
+    /// The default variant for the feature, which defaults to `off` for boolean features with omitted variants.
+    #[serde(default)]
     pub default_variant: String,
+    /// The variant returned when the feature is disabled, in place of the default variant.
+    pub off_variant: Option<String>,
+    /// The variant returned when evaluation fails, in place of the default variant.
+    pub fallback_variant: Option<String>,
+    /// The features that must evaluate to an expected variant before this feature is evaluated.
+    #[serde(default)]
+    pub prerequisites: Vec<Prerequisite>,
     #[serde(default)]
     pub audience_rules: Vec<AudienceRule>,
-    pub default_rule: DefaultRule,
+    /// The rule applied when no audience rules match, which is only optional if `require_default` is `false`.
+    pub default_rule: Option<DefaultRule>,
+    /// The context attribute whose value selects a rule from `dimension_rules`, such as `region`.
+    pub default_dimension: Option<String>,
+    /// The default rules keyed by value of the `default_dimension` attribute, which replace `default_rule` when matched.
+    #[serde(default)]
+    pub dimension_rules: BTreeMap<String, DefaultRule>,
+    /// Whether a default rule is required; otherwise the default variant is returned when no audience rules match.
+    #[serde(default = "default_require_default")]
+    pub require_default: bool,
+}
+
+impl Feature {
+    /// Applies the feature overlay, as described by `Config::merge`.
+    fn merge(&mut self, overlay: FeatureOverlay) {
+        fn replace<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+
+        replace(&mut self.enabled, overlay.enabled);
+        replace(&mut self.tags, overlay.tags);
+        replace(&mut self.salt, overlay.salt);
+        replace(&mut self.value_type, overlay.value_type);
+        replace(&mut self.default_variant, overlay.default_variant);
+        replace(&mut self.prerequisites, overlay.prerequisites);
+        replace(&mut self.require_default, overlay.require_default);
+        replace(&mut self.bucketing_key, overlay.bucketing_key.map(Some));
+        replace(&mut self.element_type, overlay.element_type.map(Some));
+        replace(&mut self.off_variant, overlay.off_variant.map(Some));
+        replace(
+            &mut self.fallback_variant,
+            overlay.fallback_variant.map(Some),
+        );
+        replace(&mut self.default_rule, overlay.default_rule.map(Some));
+        replace(
+            &mut self.default_dimension,
+            overlay.default_dimension.map(Some),
+        );
+
+        self.variants.extend(overlay.variants);
+        self.variant_metadata.extend(overlay.variant_metadata);
+        self.dimension_rules.extend(overlay.dimension_rules);
+
+        for rule in overlay.audience_rules {
+            match self.audience_rules.iter_mut().find(|r| r.name == rule.name) {
+                Some(existing) => *existing = rule,
+                None => self.audience_rules.push(rule),
+            }
+        }
+    }
+}
+
+/// A partial configuration that is applied to a base configuration with `Config::merge`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Overlay {
+    pub seed: Option<u32>,
+    pub hash_algorithm: Option<HashAlgorithm>,
+    pub hash_key_format: Option<HashKeyFormat>,
+    #[serde(default)]
+    pub features: BTreeMap<String, FeatureOverlay>,
+    #[serde(default)]
+    pub exclusion_groups: BTreeMap<String, Vec<String>>,
+}
+
+/// A partial feature configuration, where every field is optional and only specified fields are applied.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct FeatureOverlay {
+    pub enabled: Option<bool>,
+    pub tags: Option<Vec<String>>,
+    pub salt: Option<String>,
+    pub bucketing_key: Option<String>,
+    pub value_type: Option<ValueType>,
+    pub element_type: Option<ValueType>,
+    #[serde(default)]
+    pub variants: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub variant_metadata: BTreeMap<String, BTreeMap<String, String>>,
+    pub default_variant: Option<String>,
+    pub off_variant: Option<String>,
+    pub fallback_variant: Option<String>,
+    pub prerequisites: Option<Vec<Prerequisite>>,
+    #[serde(default)]
+    pub audience_rules: Vec<AudienceRule>,
+    pub default_rule: Option<DefaultRule>,
+    pub default_dimension: Option<String>,
+    #[serde(default)]
+    pub dimension_rules: BTreeMap<String, DefaultRule>,
+    pub require_default: Option<bool>,
+}
+
+impl FeatureOverlay {
+    /// Converts the overlay into a complete feature, returning the name of the first missing required field.
+    fn into_feature(self) -> Result<Feature, &'static str> {
+        Ok(Feature {
+            enabled: self.enabled.ok_or("enabled")?,
+            tags: self.tags.unwrap_or_default(),
+            salt: self.salt.unwrap_or_default(),
+            bucketing_key: self.bucketing_key,
+            value_type: self.value_type.ok_or("value_type")?,
+            element_type: self.element_type,
+            variants: self.variants,
+            variant_metadata: self.variant_metadata,
+            default_variant: self.default_variant.unwrap_or_default(),
+            off_variant: self.off_variant,
+            fallback_variant: self.fallback_variant,
+            prerequisites: self.prerequisites.unwrap_or_default(),
+            audience_rules: self.audience_rules,
+            default_rule: self.default_rule,
+            default_dimension: self.default_dimension,
+            dimension_rules: self.dimension_rules,
+            require_default: self.require_default.unwrap_or_else(default_require_default),
+        })
+    }
+}
+
+/// The configuration for a prerequisite, which requires another feature to evaluate to the specified variant.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Prerequisite {
+    pub feature: String,
+    pub variant: String,
 }
 
 /// The configuration for the default feature rule, which applies when no audience rules match.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DefaultRule {
     #[serde(flatten)]
     pub bucketing: Bucketing,
+    /// Whether the distribution weights are scaled to cover all buckets, rather than being required to total 100.
+    #[serde(default)]
+    pub normalize: bool,
 }
 
 /// The configuration for an audience rule, which applies to a specific subset of users.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AudienceRule {
     pub name: String,
-    pub expression: String,
+    #[serde(flatten)]
+    pub expression: Expression,
+    /// Whether the rule applies when the expression does not match, which makes exclusions explicit.
+    #[serde(default)]
+    pub negate: bool,
+    /// The evaluation priority of the rule, where lower numbers are evaluated first and ties preserve the configured order.
+    #[serde(default)]
+    pub priority: i32,
+    /// The RFC3339 timestamp from which the rule is applicable, compared against the `_now` context attribute.
+    pub starts_at: Option<String>,
+    /// The RFC3339 timestamp from which the rule is no longer applicable.
+    pub ends_at: Option<String>,
     #[serde(flatten)]
     pub bucketing: Bucketing,
+    /// Whether the distribution weights are scaled to cover all buckets, rather than being required to total 100.
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+/// The configuration for an audience expression, either a single expression or a list of expressions that must all or any evaluate to true.
+///
+/// Array attributes can be tested for membership with the built-in `contains` function, for example `contains(roles, "admin")`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Expression {
+    Single { expression: String },
+    All { all: Vec<String> },
+    Any { any: Vec<String> },
+}
+
+impl Expression {
+    /// Returns the expression sources.
+    fn sources(&self) -> impl Iterator<Item = &String> {
+        match self {
+            Expression::Single { expression } => std::slice::from_ref(expression).iter(),
+            Expression::All { all } => all.iter(),
+            Expression::Any { any } => any.iter(),
+        }
+    }
+
+    /// Replaces `${NAME}` variables in the expression sources with values from the lookup function.
+    fn interpolate(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> Result<(), FetaError> {
+        let sources = match self {
+            Expression::Single { expression } => std::slice::from_mut(expression),
+            Expression::All { all } => all.as_mut_slice(),
+            Expression::Any { any } => any.as_mut_slice(),
+        };
+
+        for source in sources {
+            *source = interpolate(source, lookup)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces `${NAME}` variables in the string with values from the lookup function.
+fn interpolate(s: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, FetaError> {
+    let mut output = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+
+        let Some(end) = rest[start..].find('}') else {
+            return Err(FetaError::Configuration(format!(
+                "unterminated variable in expression: {}",
+                s
+            )));
+        };
+
+        let name = &rest[start + 2..start + end];
+        let value = lookup(name).ok_or_else(|| {
+            FetaError::Configuration(format!("undefined variable in expression: {}", name))
+        })?;
+
+        output.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Returns the default schema version for configs that do not specify one.
+fn default_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// Returns the default value for `Feature::require_default`, which preserves strict validation.
+fn default_require_default() -> bool {
+    true
+}
+
+/// Looks up a variable in the process environment.
+pub(crate) fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
 }
 
 /// The configuration for how to bucket users into variants, either by specifying a single variant or by defining a distribution of variants.
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// A single variant can also be given a percentage, which serves it to that share of users and lets the rest fall through.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Bucketing {
+    // rollouts must precede variants, as untagged variants would otherwise ignore the percentage
+    Rollout { variant: String, percentage: f64 },
     Variant { variant: String },
-    Distribution { distribution: BTreeMap<String, u8> },
+    Distribution { distribution: BTreeMap<String, f64> },
+}
+
+/// Returns the path of the first field in the input that is not present in the parsed configuration.
+fn find_unknown_field(
+    input: &serde_json::Value,
+    parsed: &serde_json::Value,
+    path: &str,
+) -> Option<String> {
+    match (input, parsed) {
+        (serde_json::Value::Object(input), serde_json::Value::Object(parsed)) => {
+            input.iter().find_map(|(key, value)| {
+                let path = match path {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", path, key),
+                };
+
+                match parsed.get(key) {
+                    Some(p) => find_unknown_field(value, p, &path),
+                    None => Some(path),
+                }
+            })
+        }
+        (serde_json::Value::Array(input), serde_json::Value::Array(parsed)) => input
+            .iter()
+            .zip(parsed)
+            .enumerate()
+            .find_map(|(i, (value, p))| find_unknown_field(value, p, &format!("{}[{}]", path, i))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_json_strict() {
+        let json = r#"{
+            "features": {
+                "f1": {
+                    "enabled": true,
+                    "value_type": "boolean",
+                    "variants": { "off": false, "on": { "nested": true } },
+                    "default_variant": "off",
+                    "audience_rules": [
+                        { "name": "beta", "expression": "is_beta", "variant": "on", "percentage": 20 }
+                    ],
+                    "default_rul": { "variant": "off" }
+                }
+            }
+        }"#;
+
+        assert!(Config::from_json(json).is_ok());
+
+        let err = Config::from_json_strict(json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: unknown field: features.f1.default_rul"
+        );
+
+        let json = json.replace("default_rul", "default_rule");
+        let expected = Config::from_json(&json).unwrap();
+        assert_eq!(Config::from_json_strict(&json).unwrap(), expected);
+
+        let json = json.replace(r#""percentage": 20"#, r#""percentag": 20"#);
+        let err = Config::from_json_strict(&json).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("features.f1.audience_rules[0].percentag")
+        );
+    }
+
+    #[test]
+    fn test_config_merge() {
+        let mut config = Config::from_json(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "tags": ["team"],
+                        "value_type": "integer",
+                        "variants": { "a": 1, "b": 2 },
+                        "default_variant": "a",
+                        "audience_rules": [
+                            { "name": "beta", "expression": "is_beta", "variant": "b" },
+                            { "name": "internal", "expression": "is_internal", "variant": "b" }
+                        ],
+                        "default_rule": { "variant": "a" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let overlay: Overlay = serde_json::from_str(
+            r#"{
+                "seed": 7,
+                "features": {
+                    "f1": {
+                        "enabled": false,
+                        "variants": { "b": 3, "c": 4 },
+                        "audience_rules": [
+                            { "name": "beta", "expression": "is_beta", "variant": "c" },
+                            { "name": "alpha", "expression": "is_alpha", "variant": "c" }
+                        ]
+                    },
+                    "f2": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "variants": { "off": false, "on": true },
+                        "default_variant": "off",
+                        "default_rule": { "variant": "on" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        config.merge(overlay).expect("overlay should merge");
+        assert_eq!(config.seed, 7);
+
+        let f1 = &config.features["f1"];
+        assert!(!f1.enabled);
+        assert_eq!(f1.tags, vec!["team".to_string()]);
+        assert_eq!(
+            f1.variants,
+            BTreeMap::from([
+                ("a".to_string(), 1.into()),
+                ("b".to_string(), 3.into()),
+                ("c".to_string(), 4.into()),
+            ])
+        );
+
+        let rules: Vec<(&str, &Bucketing)> = f1
+            .audience_rules
+            .iter()
+            .map(|r| (r.name.as_str(), &r.bucketing))
+            .collect();
+        let variant = |v: &str| Bucketing::Variant {
+            variant: v.to_string(),
+        };
+        assert_eq!(
+            rules,
+            vec![
+                ("beta", &variant("c")),
+                ("internal", &variant("b")),
+                ("alpha", &variant("c")),
+            ]
+        );
+
+        let f2 = &config.features["f2"];
+        assert!(f2.enabled);
+        assert!(f2.require_default);
+        assert!(config.validate().is_ok());
+
+        let overlay: Overlay =
+            serde_json::from_str(r#"{"features": {"f3": {"enabled": true}}}"#).unwrap();
+        let err = config
+            .merge(overlay)
+            .expect_err("overlay should be invalid");
+        assert_eq!(
+            err,
+            FetaError::Configuration(
+                "overlay feature f3 is missing required field: value_type".to_string()
+            )
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_config_from_yaml() {
+        let json = r#"{
+            "seed": 7,
+            "features": {
+                "f1": {
+                    "enabled": true,
+                    "value_type": "float",
+                    "variants": { "a": 1, "b": 2.5 },
+                    "default_variant": "a",
+                    "audience_rules": [
+                        { "name": "r1", "expression": "is_beta", "variant": "b" },
+                        { "name": "r2", "all": ["is_beta", "age gt 30"], "negate": true, "distribution": { "a": 25, "b": 75 } },
+                        { "name": "r3", "any": ["is_beta"], "priority": -1, "variant": "a" }
+                    ],
+                    "default_rule": { "distribution": { "a": 50.5, "b": 49.5 } }
+                },
+                "f2": {
+                    "enabled": false,
+                    "tags": ["team"],
+                    "value_type": "array",
+                    "element_type": "string",
+                    "variants": { "a": ["x", "y"], "b": [] },
+                    "default_variant": "a",
+                    "default_rule": { "variant": "b" }
+                }
+            }
+        }"#;
+
+        let yaml = r#"
+seed: 7
+features:
+  f1:
+    enabled: true
+    value_type: float
+    variants:
+      a: 1
+      b: 2.5
+    default_variant: a
+    audience_rules:
+      - name: r1
+        expression: is_beta
+        variant: b
+      - name: r2
+        all: [is_beta, age gt 30]
+        negate: true
+        distribution:
+          a: 25
+          b: 75
+      - name: r3
+        any: [is_beta]
+        priority: -1
+        variant: a
+    default_rule:
+      distribution:
+        a: 50.5
+        b: 49.5
+  f2:
+    enabled: false
+    tags: [team]
+    value_type: array
+    element_type: string
+    variants:
+      a: [x, y]
+      b: []
+    default_variant: a
+    default_rule:
+      variant: b
+"#;
+
+        let expected: Config = serde_json::from_str(json).unwrap();
+        let actual = Config::from_yaml(yaml).unwrap();
+        assert_eq!(actual, expected);
+
+        let err = Config::from_yaml("features: [").unwrap_err();
+        assert!(matches!(err, FetaError::Configuration(_)));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_config_from_toml() {
+        let input = r#"
+seed = 7
+
+[features.f1]
+enabled = true
+value_type = "float"
+variants = { a = 1, b = 2.5 }
+default_variant = "a"
+default_rule = { distribution = { a = 50.5, b = 49.5 } }
+
+[[features.f1.audience_rules]]
+name = "r1"
+expression = "is_beta"
+variant = "b"
+
+[[features.f1.audience_rules]]
+name = "r2"
+all = ["is_beta", "age gt 30"]
+negate = true
+distribution = { a = 25, b = 75 }
+
+[features.f2]
+enabled = false
+tags = ["team"]
+value_type = "array"
+element_type = "string"
+variants = { a = ["x", "y"], b = [] }
+default_variant = "a"
+default_rule = { variant = "b" }
+"#;
+
+        let expected: Config = serde_json::from_str(
+            r#"{
+                "seed": 7,
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "float",
+                        "variants": { "a": 1, "b": 2.5 },
+                        "default_variant": "a",
+                        "audience_rules": [
+                            { "name": "r1", "expression": "is_beta", "variant": "b" },
+                            { "name": "r2", "all": ["is_beta", "age gt 30"], "negate": true, "distribution": { "a": 25, "b": 75 } }
+                        ],
+                        "default_rule": { "distribution": { "a": 50.5, "b": 49.5 } }
+                    },
+                    "f2": {
+                        "enabled": false,
+                        "tags": ["team"],
+                        "value_type": "array",
+                        "element_type": "string",
+                        "variants": { "a": ["x", "y"], "b": [] },
+                        "default_variant": "a",
+                        "default_rule": { "variant": "b" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let actual = Config::from_toml(input).unwrap();
+        assert_eq!(actual, expected);
+
+        let output = toml::to_string(&actual).unwrap();
+        assert_eq!(Config::from_toml(&output).unwrap(), expected);
+
+        let err = Config::from_toml("[features").unwrap_err();
+        assert!(matches!(err, FetaError::Configuration(_)));
+    }
 }
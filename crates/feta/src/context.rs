@@ -1,13 +1,63 @@
 use std::collections::HashMap;
 
-use mexl::Object;
+use mexl::{Environment, Object};
 use serde::{Deserialize, Serialize};
 
+use crate::error::FetaError;
+
+/// The reserved context attribute containing the current RFC3339 timestamp, used to evaluate scheduled rules.
+pub const NOW_ATTRIBUTE: &str = "_now";
+
 /// The context for a feature evaluation, including the user key and any additional attributes.
+///
+/// The user key may only be omitted when deserializing an anonymous context.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "ContextData")]
 pub struct Context {
     pub user_key: String,
+    /// The attributes for the user, where JSON numbers keep their integer or float type, so `10` and `10.0` differ.
+    ///
+    /// Numeric comparisons in expressions widen integers to floats, so `amount eq 10` matches either attribute.
     pub attributes: Option<HashMap<String, Object>>,
+    /// The request-scoped attributes shared by all features, which are overridden by `attributes` on key collision.
+    pub globals: Option<HashMap<String, Object>>,
+    /// The variant overrides for the evaluation, keyed by feature name.
+    pub overrides: Option<HashMap<String, String>>,
+    /// Whether the context is anonymous, in which case a random key is used for bucketing.
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
+/// The deserialized form of a `Context`, where the user key is optional so that it can be validated.
+#[derive(Deserialize)]
+struct ContextData {
+    user_key: Option<String>,
+    attributes: Option<HashMap<String, Object>>,
+    globals: Option<HashMap<String, Object>>,
+    overrides: Option<HashMap<String, String>>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+impl TryFrom<ContextData> for Context {
+    type Error = FetaError;
+
+    /// Converts the deserialized data into a `Context`, returning an error if a non-anonymous context has no user key.
+    fn try_from(data: ContextData) -> Result<Self, Self::Error> {
+        let user_key = match data.user_key {
+            Some(k) => k,
+            None if data.anonymous => String::new(),
+            None => return Err(FetaError::Request("user key is required".to_string())),
+        };
+
+        Ok(Self {
+            user_key,
+            attributes: data.attributes,
+            globals: data.globals,
+            overrides: data.overrides,
+            anonymous: data.anonymous,
+        })
+    }
 }
 
 impl Context {
@@ -16,8 +66,170 @@ impl Context {
         Self {
             user_key: user_key.into(),
             attributes: None,
+            globals: None,
+            overrides: None,
+            anonymous: false,
+        }
+    }
+
+    /// Creates a new anonymous `Context` with no user key or attributes.
+    pub fn anonymous() -> Self {
+        Self {
+            user_key: String::new(),
+            attributes: None,
+            globals: None,
+            overrides: None,
+            anonymous: true,
         }
     }
+
+    /// Creates a new `Context` with the given user key and attributes from a JSON object, such as a request body.
+    ///
+    /// Nested objects and arrays are converted in the same way as when deserializing a `Context`.
+    pub fn with_json_attributes(
+        user_key: impl Into<String>,
+        attributes: serde_json::Value,
+    ) -> Result<Self, FetaError> {
+        if !attributes.is_object() {
+            return Err(FetaError::Request(
+                "attributes must be a JSON object".to_string(),
+            ));
+        }
+
+        let attributes: HashMap<String, Object> = serde_json::from_value(attributes)
+            .map_err(|e| FetaError::Request(format!("invalid attributes: {}", e)))?;
+
+        Ok(Self {
+            attributes: (!attributes.is_empty()).then_some(attributes),
+            ..Self::new(user_key)
+        })
+    }
+
+    /// Converts string attributes and globals that contain a number into integers or floats, so that they can be
+    /// compared numerically in audience expressions, such as `orders gt 5` where `orders` was sent as `"10"`.
+    ///
+    /// This applies to all top-level string values, so it should not be used where numeric strings such as
+    /// identifiers must be compared as strings. Non-finite values such as `"NaN"` are left unchanged.
+    pub fn coerce_numeric(mut self) -> Self {
+        for attributes in [&mut self.attributes, &mut self.globals]
+            .into_iter()
+            .flatten()
+        {
+            for value in attributes.values_mut() {
+                if let Object::String(s) = value
+                    && let Some(n) = parse_numeric(s)
+                {
+                    *value = n;
+                }
+            }
+        }
+        self
+    }
+
+    /// Retrieves the attribute with the specified key, falling back to the global attributes.
+    pub fn attribute(&self, key: &str) -> Option<&Object> {
+        self.attributes
+            .as_ref()
+            .and_then(|a| a.get(key))
+            .or_else(|| self.globals.as_ref().and_then(|g| g.get(key)))
+    }
+
+    /// Creates the expression `Environment` for the context, with attributes taking precedence over globals.
+    ///
+    /// The environment can be created once and reused with `decide_with_env` when evaluating multiple features.
+    pub fn environment(&self) -> Environment {
+        let mut env = Environment::default();
+        for attributes in [&self.globals, &self.attributes].into_iter().flatten() {
+            for (key, value) in attributes {
+                env.set(key, value.clone());
+            }
+        }
+        env
+    }
+}
+
+/// Parses a string as an integer or a finite float, returning `None` if it is not numeric.
+fn parse_numeric(s: &str) -> Option<Object> {
+    if let Ok(i) = s.parse::<i64>() {
+        return Some(Object::Integer(i));
+    }
+
+    s.parse::<f64>()
+        .ok()
+        .filter(|f| f.is_finite())
+        .map(Object::Float)
+}
+
+/// The builder for constructing a `Context` instance.
+#[derive(Debug, Default)]
+pub struct ContextBuilder {
+    user_key: Option<String>,
+    attributes: HashMap<String, Object>,
+    globals: HashMap<String, Object>,
+    anonymous: bool,
+}
+
+impl ContextBuilder {
+    /// Creates a new `ContextBuilder` with no user key or attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the user key for the context.
+    pub fn user_key(mut self, user_key: impl Into<String>) -> Self {
+        self.user_key = Some(user_key.into());
+        self
+    }
+
+    /// Marks the context as anonymous, removing the requirement for a user key.
+    pub fn anonymous(mut self) -> Self {
+        self.anonymous = true;
+        self
+    }
+
+    /// Adds an attribute with the specified key and value to the context.
+    pub fn attribute(mut self, key: impl Into<String>, value: impl Into<Object>) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds a string attribute with the specified key and value to the context.
+    pub fn string_attribute(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attribute(key, Object::String(value.into()))
+    }
+
+    /// Adds an integer attribute with the specified key and value to the context.
+    pub fn int_attribute(self, key: impl Into<String>, value: i64) -> Self {
+        self.attribute(key, Object::Integer(value))
+    }
+
+    /// Adds a boolean attribute with the specified key and value to the context.
+    pub fn bool_attribute(self, key: impl Into<String>, value: bool) -> Self {
+        self.attribute(key, Object::Boolean(value))
+    }
+
+    /// Adds a global attribute with the specified key and value to the context.
+    pub fn global(mut self, key: impl Into<String>, value: impl Into<Object>) -> Self {
+        self.globals.insert(key.into(), value.into());
+        self
+    }
+
+    /// Builds the `Context` instance with the current values.
+    pub fn build(self) -> Result<Context, FetaError> {
+        let user_key = match self.user_key {
+            Some(k) => k,
+            None if self.anonymous => String::new(),
+            None => return Err(FetaError::Request("user key is required".to_string())),
+        };
+
+        Ok(Context {
+            user_key,
+            attributes: (!self.attributes.is_empty()).then_some(self.attributes),
+            globals: (!self.globals.is_empty()).then_some(self.globals),
+            overrides: None,
+            anonymous: self.anonymous,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -30,5 +242,233 @@ mod tests {
         let ctx = Context::new(KEY);
         assert_eq!(ctx.user_key, KEY);
         assert!(ctx.attributes.is_none());
+        assert!(ctx.globals.is_none());
+        assert!(ctx.overrides.is_none());
+        assert!(!ctx.anonymous);
+    }
+
+    #[test]
+    fn test_context_builder() {
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .attribute("float", Object::Float(1.5))
+            .string_attribute("email", "user@company.com")
+            .int_attribute("orders", 10)
+            .bool_attribute("is_beta", true)
+            .build()
+            .expect("context should build");
+
+        let expected: HashMap<String, Object> = serde_json::from_str(
+            r#"{"float": 1.5, "email": "user@company.com", "orders": 10, "is_beta": true}"#,
+        )
+        .expect("should deserialize");
+
+        assert_eq!(ctx.user_key, "key");
+        assert_eq!(ctx.attributes, Some(expected));
+    }
+
+    #[test]
+    fn test_context_builder_no_attributes() {
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .build()
+            .expect("context should build");
+
+        assert!(ctx.attributes.is_none());
+    }
+
+    #[test]
+    fn test_context_globals() {
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .string_attribute("region", "eu")
+            .global("region", Object::String("us".to_string()))
+            .global("deployment", Object::String("canary".to_string()))
+            .build()
+            .expect("context should build");
+
+        assert_eq!(
+            ctx.attribute("region"),
+            Some(&Object::String("eu".to_string()))
+        );
+        assert_eq!(
+            ctx.attribute("deployment"),
+            Some(&Object::String("canary".to_string()))
+        );
+        assert_eq!(ctx.attribute("invalid"), None);
+
+        let program = mexl::compile(r#"region eq "eu" and deployment eq "canary""#).unwrap();
+        let actual = mexl::run(&program, &ctx.environment()).unwrap();
+        assert_eq!(actual, Object::Boolean(true));
+    }
+
+    #[test]
+    fn test_context_array_attributes() {
+        let ctx: Context = serde_json::from_str(
+            r#"{"user_key": "a", "attributes": {"roles": ["admin", "user"]}}"#,
+        )
+        .expect("should deserialize");
+
+        assert_eq!(
+            ctx.attribute("roles"),
+            Some(&Object::Array(vec!["admin".into(), "user".into()]))
+        );
+
+        let tests = vec![
+            (r#"contains(roles, "admin")"#, true),
+            (r#"contains(roles, "guest")"#, false),
+            (r#"contains(groups, "admin")"#, false),
+        ];
+
+        for (input, expected) in tests {
+            let rule = crate::RuleBuilder::new()
+                .variant("a", 100)
+                .audience("admins", input)
+                .build()
+                .unwrap();
+            let actual = rule.is_applicable(&ctx, None).unwrap();
+            assert_eq!(actual, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_context_number_precision() {
+        let json = r#"{"user_key": "a", "attributes": {"count": 10, "amount": 10.0}}"#;
+        let from_str: Context = serde_json::from_str(json).expect("should deserialize");
+        let from_value =
+            Context::with_json_attributes("a", serde_json::json!({"count": 10, "amount": 10.0}))
+                .expect("context should build");
+
+        for ctx in [from_str, from_value] {
+            assert_eq!(ctx.attribute("count"), Some(&Object::Integer(10)));
+            assert_eq!(ctx.attribute("amount"), Some(&Object::Float(10.0)));
+
+            let env = ctx.environment();
+            let tests = vec![
+                ("count eq 10", true),
+                ("amount eq 10.0", true),
+                ("amount eq 10", true),
+                ("amount gt 9.5", true),
+                ("count lt 10.5", true),
+                ("amount ne count", false),
+            ];
+
+            for (input, expected) in tests {
+                let program = mexl::compile(input).unwrap();
+                let actual = mexl::run(&program, &env).unwrap();
+                assert_eq!(actual, Object::Boolean(expected), "{}", input);
+            }
+        }
+
+        let json = serde_json::to_string(
+            &Context::with_json_attributes("a", serde_json::json!({"amount": 10.0})).unwrap(),
+        )
+        .unwrap();
+        assert!(json.contains(r#""amount":10.0"#), "{}", json);
+    }
+
+    #[test]
+    fn test_context_with_json_attributes() {
+        let body = serde_json::json!({
+            "email": "user@company.com",
+            "orders": 10,
+            "score": 1.5,
+            "is_beta": true,
+            "roles": ["admin", "user"],
+            "address": {"country": "uk"},
+        });
+
+        let ctx = Context::with_json_attributes("key", body).expect("context should build");
+        assert_eq!(ctx.user_key, "key");
+        assert_eq!(ctx.attribute("orders"), Some(&Object::Integer(10)));
+        assert_eq!(ctx.attribute("score"), Some(&Object::Float(1.5)));
+        assert_eq!(ctx.attribute("is_beta"), Some(&Object::Boolean(true)));
+
+        let program = mexl::compile(
+            r#"email ew "@company.com" and orders eq 10 and is_beta and "admin" in roles"#,
+        )
+        .unwrap();
+        let actual = mexl::run(&program, &ctx.environment()).unwrap();
+        assert_eq!(actual, Object::Boolean(true));
+
+        let ctx = Context::with_json_attributes("key", serde_json::json!({}))
+            .expect("context should build");
+        assert!(ctx.attributes.is_none());
+
+        let result = Context::with_json_attributes("key", serde_json::json!(["admin"]));
+        assert!(matches!(result, Err(FetaError::Request(_))));
+    }
+
+    #[test]
+    fn test_context_coerce_numeric() {
+        let body = serde_json::json!({
+            "orders": "10",
+            "score": "1.5",
+            "email": "user@company.com",
+            "limit": "NaN",
+            "is_beta": true,
+        });
+
+        let ctx = Context::with_json_attributes("key", body).expect("context should build");
+        let program = mexl::compile("orders gt 5").unwrap();
+        let actual = mexl::run(&program, &ctx.environment());
+        assert!(!matches!(actual, Ok(Object::Boolean(true))));
+
+        let ctx = ctx.coerce_numeric();
+        let actual = mexl::run(&program, &ctx.environment()).unwrap();
+        assert_eq!(actual, Object::Boolean(true));
+
+        let tests = vec![
+            ("orders", Object::Integer(10)),
+            ("score", Object::Float(1.5)),
+            ("email", Object::String("user@company.com".to_string())),
+            ("limit", Object::String("NaN".to_string())),
+            ("is_beta", Object::Boolean(true)),
+        ];
+
+        for (key, expected) in tests {
+            assert_eq!(ctx.attribute(key), Some(&expected), "{}", key);
+        }
+
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .global("region", "2")
+            .build()
+            .expect("context should build")
+            .coerce_numeric();
+        assert_eq!(ctx.attribute("region"), Some(&Object::Integer(2)));
+    }
+
+    #[test]
+    fn test_context_anonymous() {
+        let ctx: Context =
+            serde_json::from_str(r#"{"anonymous": true}"#).expect("should deserialize");
+        assert!(ctx.anonymous);
+        assert!(ctx.user_key.is_empty());
+
+        // the user key is only optional for anonymous contexts
+        for input in [r#"{}"#, r#"{"anonymous": false}"#] {
+            let err =
+                serde_json::from_str::<Context>(input).expect_err("user key should be required");
+            assert!(
+                err.to_string().contains("user key is required"),
+                "{}",
+                input
+            );
+        }
+
+        let ctx = ContextBuilder::new()
+            .anonymous()
+            .build()
+            .expect("context should build");
+        assert!(ctx.anonymous);
+    }
+
+    #[test]
+    fn test_context_builder_errors() {
+        let result = ContextBuilder::new()
+            .bool_attribute("is_beta", true)
+            .build();
+        assert!(result.is_err());
     }
 }
@@ -1,8 +1,8 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{Value, error::FetaError};
+use crate::{Value, ValueType, error::FetaError, hash};
 
 /// The reason for a feature decision.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -14,6 +14,10 @@ pub enum Reason {
     Split,
     Match,
     MatchSplit,
+    Override,
+    Prerequisite,
+    NoMatch,
+    Excluded,
     Error,
 }
 
@@ -27,31 +31,138 @@ impl fmt::Display for Reason {
             Self::Split => "split",
             Self::Match => "match",
             Self::MatchSplit => "match_split",
+            Self::Override => "override",
+            Self::Prerequisite => "prerequisite",
+            Self::NoMatch => "no_match",
+            Self::Excluded => "excluded",
             Self::Error => "error",
         };
         f.write_str(str)
     }
 }
 
+impl FromStr for Reason {
+    type Err = FetaError;
+
+    /// Parses a `Reason` from the string returned by `Display`, returning an error for unknown values.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unknown" => Ok(Self::Unknown),
+            "disabled" => Ok(Self::Disabled),
+            "static" => Ok(Self::Static),
+            "split" => Ok(Self::Split),
+            "match" => Ok(Self::Match),
+            "match_split" => Ok(Self::MatchSplit),
+            "override" => Ok(Self::Override),
+            "prerequisite" => Ok(Self::Prerequisite),
+            "no_match" => Ok(Self::NoMatch),
+            "excluded" => Ok(Self::Excluded),
+            "error" => Ok(Self::Error),
+            _ => Err(FetaError::Request(format!("unknown reason: {}", s))),
+        }
+    }
+}
+
 /// The result of a feature evaluation, including the variant, reason, and any error information.
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct Decision {
+    /// The hash of the bucketing key, which is zero for disabled features as they are not bucketed.
     pub hash: u32,
+    /// The bucket that the hash maps to, which determines the variant for percentage rollouts.
+    pub bucket: u32,
     pub variant: String,
     pub reason: Reason,
     pub value: Value,
+    /// The declared value type of the feature, which is `None` if the feature does not exist.
+    pub value_type: Option<ValueType>,
     pub audience: Option<String>,
+    /// The error that caused the decision to fail, which is only set for the `error` reason.
     pub error: Option<FetaError>,
+    /// A non-fatal problem with a successful decision, such as a missing bucketing key attribute.
+    pub warning: Option<FetaError>,
+    /// Whether the decision was made for an anonymous context using an ephemeral bucketing key.
+    pub anonymous: bool,
+    /// Whether the decision was made by the default rule because no audience rule applied.
+    pub is_default: bool,
+    /// The index of the rule that produced the decision in evaluation order, where the default rule is last.
+    pub rule_index: Option<usize>,
+    /// The metadata of the variant, such as a display name or analytics ID, if any is configured.
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl Decision {
+    /// Returns `true` if the decision failed, which is the case if and only if the reason is `Reason::Error`.
+    /// A successful decision may carry a non-fatal `warning`.
+    pub fn is_error(&self) -> bool {
+        self.reason == Reason::Error
+    }
+
+    /// Returns `true` if the feature was disabled.
+    pub fn is_disabled(&self) -> bool {
+        self.reason == Reason::Disabled
+    }
+
+    /// Returns `true` if the decision did not fail, including when the feature was disabled.
+    pub fn is_success(&self) -> bool {
+        !self.is_error()
+    }
+
+    /// Returns the boolean value, or the specified default if the decision failed or the value is not a boolean.
+    pub fn bool_or(self, default: bool) -> bool {
+        self.success_value()
+            .and_then(Value::as_bool)
+            .unwrap_or(default)
+    }
+
+    /// Returns the integer value, or the specified default if the decision failed or the value is not an integer.
+    pub fn int_or(self, default: i64) -> i64 {
+        self.success_value()
+            .and_then(Value::as_i64)
+            .unwrap_or(default)
+    }
+
+    /// Returns the float value, or the specified default if the decision failed or the value is not a number.
+    ///
+    /// Integer values are widened to floats, consistent with `Value::as_f64`.
+    pub fn float_or(self, default: f64) -> f64 {
+        self.success_value()
+            .and_then(Value::as_f64)
+            .unwrap_or(default)
+    }
+
+    /// Returns the string value, or the specified default if the decision failed or the value is not a string.
+    pub fn string_or(self, default: impl Into<String>) -> String {
+        if self.is_error() {
+            return default.into();
+        }
+
+        match self.value {
+            Value::String(s) => s,
+            _ => default.into(),
+        }
+    }
+
+    /// Returns the value if the decision did not fail.
+    fn success_value(&self) -> Option<&Value> {
+        self.is_success().then_some(&self.value)
+    }
 }
 
 /// A builder for constructing `Decision` instances.
 pub struct DecisionBuilder {
     hash: u32,
+    bucket: u32,
     variant: Option<String>,
     reason: Reason,
     value: Value,
+    value_type: Option<ValueType>,
     audience: Option<String>,
     error: Option<FetaError>,
+    warning: Option<FetaError>,
+    anonymous: bool,
+    is_default: bool,
+    rule_index: Option<usize>,
+    metadata: Option<BTreeMap<String, String>>,
 }
 
 impl DecisionBuilder {
@@ -59,23 +170,31 @@ impl DecisionBuilder {
     pub fn new() -> Self {
         Self {
             hash: 0,
+            bucket: 0,
             variant: None,
             reason: Reason::Unknown,
             value: Value::Null,
+            value_type: None,
             audience: None,
             error: None,
+            warning: None,
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
         }
     }
 
-    /// Sets the hash value for the decision.
+    /// Sets the hash value for the decision, along with the bucket that it maps to.
     pub fn hash(mut self, hash: u32) -> Self {
         self.hash = hash;
+        self.bucket = hash::to_bucket(hash);
         self
     }
 
-    /// Sets the variant for the decision.
-    pub fn variant(mut self, variant: &str) -> Self {
-        self.variant = Some(variant.to_string());
+    /// Sets the variant for the decision, taking ownership of the string if one is provided.
+    pub fn variant(mut self, variant: impl Into<String>) -> Self {
+        self.variant = Some(variant.into());
         self
     }
 
@@ -85,9 +204,45 @@ impl DecisionBuilder {
         self
     }
 
-    /// Sets the audience for the decision.
-    pub fn audience(mut self, audience: &str) -> Self {
-        self.audience = Some(audience.to_string());
+    /// Sets the declared value type of the feature for the decision.
+    pub fn value_type(mut self, value_type: ValueType) -> Self {
+        self.value_type = Some(value_type);
+        self
+    }
+
+    /// Sets the audience for the decision, taking ownership of the string if one is provided.
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Sets whether the decision was made for an anonymous context.
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    /// Sets whether the decision was made by the default rule.
+    pub fn is_default(mut self, is_default: bool) -> Self {
+        self.is_default = is_default;
+        self
+    }
+
+    /// Sets the index of the rule that produced the decision.
+    pub fn rule_index(mut self, rule_index: usize) -> Self {
+        self.rule_index = Some(rule_index);
+        self
+    }
+
+    /// Sets the metadata of the variant for the decision.
+    pub fn metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Sets a non-fatal warning for the decision, which is reported without changing the reason.
+    pub fn warning(mut self, err: FetaError) -> Self {
+        self.warning = Some(err);
         self
     }
 
@@ -114,11 +269,18 @@ impl DecisionBuilder {
     fn build(self) -> Decision {
         Decision {
             hash: self.hash,
+            bucket: self.bucket,
             variant: self.variant.unwrap_or_default(),
             reason: self.reason,
             value: self.value,
+            value_type: self.value_type,
             audience: self.audience,
             error: self.error,
+            warning: self.warning,
+            anonymous: self.anonymous,
+            is_default: self.is_default,
+            rule_index: self.rule_index,
+            metadata: self.metadata,
         }
     }
 }
@@ -143,6 +305,10 @@ mod tests {
             (Reason::Split, "split"),
             (Reason::Match, "match"),
             (Reason::MatchSplit, "match_split"),
+            (Reason::Override, "override"),
+            (Reason::Prerequisite, "prerequisite"),
+            (Reason::NoMatch, "no_match"),
+            (Reason::Excluded, "excluded"),
             (Reason::Error, "error"),
         ];
 
@@ -152,6 +318,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reason_from_str() {
+        let tests = vec![
+            Reason::Unknown,
+            Reason::Disabled,
+            Reason::Static,
+            Reason::Split,
+            Reason::Match,
+            Reason::MatchSplit,
+            Reason::Override,
+            Reason::Prerequisite,
+            Reason::NoMatch,
+            Reason::Excluded,
+            Reason::Error,
+        ];
+
+        for input in tests {
+            let actual: Reason = input.to_string().parse().expect("reason should parse");
+            assert_eq!(actual, input);
+        }
+
+        for input in ["", "Match", "match-split", "invalid"] {
+            let err = input
+                .parse::<Reason>()
+                .expect_err("reason should be invalid");
+            assert!(matches!(err, FetaError::Request(_)));
+        }
+    }
+
     #[test]
     fn test_reason_serialize() {
         let input = vec![
@@ -161,11 +356,79 @@ mod tests {
             Reason::Split,
             Reason::Match,
             Reason::MatchSplit,
+            Reason::Override,
+            Reason::Prerequisite,
+            Reason::NoMatch,
+            Reason::Excluded,
             Reason::Error,
         ];
         let actual = serde_json::to_string(&input).expect("should serialize");
-        let expected = r#"["unknown","disabled","static","split","match","match_split","error"]"#;
+        let expected = r#"["unknown","disabled","static","split","match","match_split","override","prerequisite","no_match","excluded","error"]"#;
         assert_eq!(actual, expected);
+
+        let actual: Vec<Reason> = serde_json::from_str(expected).expect("should deserialize");
+        assert_eq!(actual, input);
+    }
+
+    #[test]
+    fn test_decision_value_or() {
+        let success = |value: Value| DecisionBuilder::new().value(value).success(Reason::Static);
+        let error = |value: Value| {
+            DecisionBuilder::new()
+                .value(value)
+                .error(FetaError::NotFound(String::new()))
+        };
+
+        assert!(success(true.into()).bool_or(false));
+        assert!(success(1.into()).bool_or(true));
+        assert!(error(false.into()).bool_or(true));
+
+        assert_eq!(success(2.into()).int_or(1), 2);
+        assert_eq!(success(2.5.into()).int_or(1), 1);
+        assert_eq!(error(2.into()).int_or(1), 1);
+
+        assert_eq!(success(2.5.into()).float_or(1.0), 2.5);
+        assert_eq!(success(2.into()).float_or(1.0), 2.0);
+        assert_eq!(success("a".into()).float_or(1.0), 1.0);
+        assert_eq!(error(2.5.into()).float_or(1.0), 1.0);
+
+        assert_eq!(success("a".into()).string_or("b"), "a");
+        assert_eq!(success(true.into()).string_or("b"), "b");
+        assert_eq!(error("a".into()).string_or("b"), "b");
+
+        // disabled decisions return the off value rather than the default
+        let disabled = DecisionBuilder::new().value(true.into()).disabled();
+        assert!(disabled.bool_or(false));
+    }
+
+    #[test]
+    fn test_decision_outcome() {
+        let tests = vec![
+            (Reason::Unknown, false, false),
+            (Reason::Disabled, false, true),
+            (Reason::Static, false, false),
+            (Reason::Split, false, false),
+            (Reason::Match, false, false),
+            (Reason::MatchSplit, false, false),
+            (Reason::Override, false, false),
+            (Reason::Prerequisite, false, false),
+            (Reason::NoMatch, false, false),
+            (Reason::Excluded, false, false),
+            (Reason::Error, true, false),
+        ];
+
+        for (reason, is_error, is_disabled) in tests {
+            let actual = DecisionBuilder::new().success(reason);
+            assert_eq!(actual.is_error(), is_error);
+            assert_eq!(actual.is_disabled(), is_disabled);
+            assert_eq!(actual.is_success(), !is_error);
+        }
+
+        let actual = DecisionBuilder::new()
+            .warning(FetaError::Request(String::new()))
+            .success(Reason::Split);
+        assert!(actual.is_success());
+        assert!(!actual.is_error());
     }
 
     #[test]
@@ -174,15 +437,23 @@ mod tests {
             .hash(1)
             .variant("var")
             .value(true.into())
+            .value_type(ValueType::Boolean)
             .audience("aud")
             .success(Reason::Match);
         let expected = Decision {
             hash: 1,
+            bucket: 100,
             variant: "var".to_string(),
             reason: Reason::Match,
             value: true.into(),
+            value_type: Some(ValueType::Boolean),
             audience: Some("aud".to_string()),
             error: None,
+            warning: None,
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
         };
         assert_eq!(actual, expected);
     }
@@ -196,11 +467,18 @@ mod tests {
             .disabled();
         let expected = Decision {
             hash: 1,
+            bucket: 100,
             variant: "var".to_string(),
             reason: Reason::Disabled,
             value: true.into(),
+            value_type: None,
             audience: None,
             error: None,
+            warning: None,
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
         };
         assert_eq!(actual, expected);
     }
@@ -215,11 +493,97 @@ mod tests {
             .error(err.clone());
         let expected = Decision {
             hash: 1,
+            bucket: 100,
             variant: "var".to_string(),
             reason: Reason::Error,
             value: true.into(),
+            value_type: None,
             audience: None,
             error: Some(err),
+            warning: None,
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_decision_builder_warning() {
+        let err = FetaError::Request(String::new());
+        let actual = DecisionBuilder::new()
+            .hash(1)
+            .variant("var")
+            .value(true.into())
+            .warning(err.clone())
+            .success(Reason::Split);
+        let expected = Decision {
+            hash: 1,
+            bucket: 100,
+            variant: "var".to_string(),
+            reason: Reason::Split,
+            value: true.into(),
+            value_type: None,
+            audience: None,
+            error: None,
+            warning: Some(err),
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_decision_builder_anonymous() {
+        let actual = DecisionBuilder::new()
+            .hash(1)
+            .variant("var")
+            .value(true.into())
+            .anonymous(true)
+            .success(Reason::Split);
+        let expected = Decision {
+            hash: 1,
+            bucket: 100,
+            variant: "var".to_string(),
+            reason: Reason::Split,
+            value: true.into(),
+            value_type: None,
+            audience: None,
+            error: None,
+            warning: None,
+            anonymous: true,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_decision_builder_is_default() {
+        let actual = DecisionBuilder::new()
+            .hash(1)
+            .variant("var")
+            .value(true.into())
+            .is_default(true)
+            .success(Reason::Split);
+        let expected = Decision {
+            hash: 1,
+            bucket: 100,
+            variant: "var".to_string(),
+            reason: Reason::Split,
+            value: true.into(),
+            value_type: None,
+            audience: None,
+            error: None,
+            warning: None,
+            anonymous: false,
+            is_default: true,
+            rule_index: None,
+            metadata: None,
         };
         assert_eq!(actual, expected);
     }
@@ -235,11 +599,18 @@ mod tests {
 
         let expected = Decision {
             hash: 1,
+            bucket: 100,
             variant: "var".to_string(),
             reason: Reason::Match,
             value: true.into(),
+            value_type: None,
             audience: Some("aud".to_string()),
             error: None,
+            warning: None,
+            anonymous: false,
+            is_default: false,
+            rule_index: None,
+            metadata: None,
         };
         assert_eq!(actual, expected);
     }
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::config;
+
+/// A field of a feature that differs between two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureChange {
+    Enabled,
+    Tags,
+    Salt,
+    BucketingKey,
+    ValueType,
+    Variants,
+    VariantMetadata,
+    DefaultVariant,
+    OffVariant,
+    FallbackVariant,
+    Prerequisites,
+    AudienceRules,
+    DefaultRule,
+    RequireDefault,
+}
+
+/// The differences between two sets of features, which can be rendered by tools when promoting configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FeaturesDiff {
+    /// The features that only exist in the other configuration.
+    pub added: Vec<String>,
+    /// The features that only exist in the current configuration.
+    pub removed: Vec<String>,
+    /// The features that exist in both configurations, with the fields that differ.
+    pub modified: BTreeMap<String, Vec<FeatureChange>>,
+    /// Whether the seed or hash algorithm differs, which changes the bucket of every user.
+    pub hashing_changed: bool,
+    /// Whether the exclusion groups differ, which changes the features that users are assigned to.
+    pub exclusion_groups_changed: bool,
+}
+
+impl FeaturesDiff {
+    /// Returns `true` if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && !self.hashing_changed
+            && !self.exclusion_groups_changed
+    }
+}
+
+/// Compares two configurations, reporting the changes from `current` to `other`.
+pub(crate) fn diff(current: &config::Config, other: &config::Config) -> FeaturesDiff {
+    let mut diff = FeaturesDiff {
+        hashing_changed: current.seed != other.seed
+            || current.hash_algorithm != other.hash_algorithm
+            || current.hash_key_format != other.hash_key_format,
+        exclusion_groups_changed: current.exclusion_groups != other.exclusion_groups,
+        ..Default::default()
+    };
+
+    for (name, feature) in &current.features {
+        match other.features.get(name) {
+            Some(o) => {
+                let changes = diff_feature(feature, o);
+                if !changes.is_empty() {
+                    diff.modified.insert(name.clone(), changes);
+                }
+            }
+            None => diff.removed.push(name.clone()),
+        }
+    }
+
+    diff.added = other
+        .features
+        .keys()
+        .filter(|name| !current.features.contains_key(*name))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+/// Compares two feature configurations, returning the fields that differ.
+fn diff_feature(current: &config::Feature, other: &config::Feature) -> Vec<FeatureChange> {
+    let checks = [
+        (current.enabled != other.enabled, FeatureChange::Enabled),
+        (current.tags != other.tags, FeatureChange::Tags),
+        (current.salt != other.salt, FeatureChange::Salt),
+        (
+            current.bucketing_key != other.bucketing_key,
+            FeatureChange::BucketingKey,
+        ),
+        (
+            current.value_type != other.value_type || current.element_type != other.element_type,
+            FeatureChange::ValueType,
+        ),
+        (current.variants != other.variants, FeatureChange::Variants),
+        (
+            current.variant_metadata != other.variant_metadata,
+            FeatureChange::VariantMetadata,
+        ),
+        (
+            current.default_variant != other.default_variant,
+            FeatureChange::DefaultVariant,
+        ),
+        (
+            current.off_variant != other.off_variant,
+            FeatureChange::OffVariant,
+        ),
+        (
+            current.fallback_variant != other.fallback_variant,
+            FeatureChange::FallbackVariant,
+        ),
+        (
+            current.prerequisites != other.prerequisites,
+            FeatureChange::Prerequisites,
+        ),
+        (
+            current.audience_rules != other.audience_rules,
+            FeatureChange::AudienceRules,
+        ),
+        (
+            current.default_rule != other.default_rule
+                || current.default_dimension != other.default_dimension
+                || current.dimension_rules != other.dimension_rules,
+            FeatureChange::DefaultRule,
+        ),
+        (
+            current.require_default != other.require_default,
+            FeatureChange::RequireDefault,
+        ),
+    ];
+
+    checks
+        .into_iter()
+        .filter_map(|(changed, change)| changed.then_some(change))
+        .collect()
+}
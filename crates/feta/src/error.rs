@@ -1,8 +1,10 @@
-use serde::Serialize;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
 use thiserror::Error;
 
 /// The error type for feta, representing various kinds of errors that can occur during feature evaluation.
-#[derive(Error, Debug, Clone, PartialEq, Serialize)]
+///
+/// Errors serialize as an object with the stable `code` and the error `message`, without the `Display` prefix.
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum FetaError {
     /// An error that occurs when the configuration is invalid or cannot be loaded.
     #[error("Configuration error: {0}")]
@@ -10,7 +12,86 @@ pub enum FetaError {
     /// An error that occurs when the request is invalid or cannot be processed.
     #[error("Request error: {0}")]
     Request(String),
+    /// An error that occurs when the requested feature does not exist.
+    #[error("Not found error: {0}")]
+    NotFound(String),
     /// An error that occurs when there is an issue with audience evaluation.
     #[error("Targeting error: {0}")]
     Targeting(String),
 }
+
+impl FetaError {
+    /// Returns a stable code identifying the error kind, which does not change with the `Display` text.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Configuration(_) => "configuration",
+            Self::Request(_) => "request",
+            Self::NotFound(_) => "not_found",
+            Self::Targeting(_) => "targeting",
+        }
+    }
+
+    /// Returns the error message, without the error kind prefix.
+    fn message(&self) -> &str {
+        match self {
+            Self::Configuration(m) | Self::Request(m) | Self::NotFound(m) | Self::Targeting(m) => m,
+        }
+    }
+
+    /// Prefixes the error message with the specified context, preserving the error kind.
+    pub(crate) fn with_context(self, context: &str) -> Self {
+        match self {
+            Self::Configuration(m) => Self::Configuration(format!("{}: {}", context, m)),
+            Self::Request(m) => Self::Request(format!("{}: {}", context, m)),
+            Self::NotFound(m) => Self::NotFound(format!("{}: {}", context, m)),
+            Self::Targeting(m) => Self::Targeting(format!("{}: {}", context, m)),
+        }
+    }
+}
+
+impl Serialize for FetaError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FetaError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        let tests = vec![
+            (FetaError::Configuration("m".to_string()), "configuration"),
+            (FetaError::Request("m".to_string()), "request"),
+            (FetaError::NotFound("m".to_string()), "not_found"),
+            (FetaError::Targeting("m".to_string()), "targeting"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(input.code(), expected);
+
+            let actual = serde_json::to_string(&input).expect("should serialize");
+            assert_eq!(
+                actual,
+                format!(r#"{{"code":"{}","message":"m"}}"#, expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_with_context() {
+        let actual = FetaError::Targeting("invalid expression".to_string()).with_context("f1");
+        assert_eq!(
+            actual,
+            FetaError::Targeting("f1: invalid expression".to_string())
+        );
+        assert_eq!(
+            actual.to_string(),
+            "Targeting error: f1: invalid expression"
+        );
+    }
+}
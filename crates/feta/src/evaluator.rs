@@ -0,0 +1,87 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{context::Context, decision::Decision, features::Features};
+
+/// A source of feature decisions, allowing code to be written against `Features` or a wrapper or test double.
+pub trait Evaluator {
+    /// Evaluates the specified feature for the given context.
+    fn decide(&self, feature: &str, ctx: &Context) -> Decision;
+
+    /// Evaluates all features for the given context.
+    fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision>;
+}
+
+impl Evaluator for Features {
+    fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        Features::decide(self, feature, ctx)
+    }
+
+    fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        Features::decide_all(self, ctx)
+    }
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for Arc<E> {
+    fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        (**self).decide(feature, ctx)
+    }
+
+    fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        (**self).decide_all(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecisionBuilder, Reason, Value};
+
+    /// An evaluator that serves the same variant for every feature.
+    struct StaticEvaluator(&'static str);
+
+    impl Evaluator for StaticEvaluator {
+        fn decide(&self, _feature: &str, _ctx: &Context) -> Decision {
+            DecisionBuilder::new()
+                .variant(self.0)
+                .value(Value::Boolean(true))
+                .success(Reason::Static)
+        }
+
+        fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+            HashMap::from([("f1".to_string(), self.decide("f1", ctx))])
+        }
+    }
+
+    /// Returns the variant for the feature, as a handler that is generic over the evaluator would.
+    fn variant_for(evaluator: &impl Evaluator, feature: &str) -> String {
+        evaluator.decide(feature, &Context::new("a")).variant
+    }
+
+    #[test]
+    fn test_evaluator() {
+        let features = Features::from_json(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "integer",
+                        "variants": { "a": 1 },
+                        "default_variant": "a",
+                        "default_rule": { "variant": "a" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(variant_for(&features, "f1"), "a");
+
+        let shared = Arc::new(features);
+        assert_eq!(variant_for(&shared, "f1"), "a");
+        assert_eq!(Evaluator::decide_all(&shared, &Context::new("a")).len(), 1);
+
+        let dynamic: Arc<dyn Evaluator + Send + Sync> = Arc::new(StaticEvaluator("on"));
+        assert_eq!(variant_for(&dynamic, "f1"), "on");
+        assert_eq!(dynamic.decide_all(&Context::new("a")).len(), 1);
+    }
+}
@@ -1,13 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use mexl::Environment;
+use chrono::{DateTime, FixedOffset};
+use mexl::{Environment, Object};
 
 use crate::{
     RuleBuilder, config,
-    context::Context,
-    decision::{Decision, DecisionBuilder},
+    context::{Context, NOW_ATTRIBUTE},
+    decision::{Decision, DecisionBuilder, Reason},
     error::FetaError,
-    hash,
+    function::Functions,
+    hash::{self, HashAlgorithm, HashKeyFormat},
     rule::Rule,
     value::{Value, ValueType},
 };
@@ -16,11 +18,25 @@ use crate::{
 pub struct FeatureBuilder {
     name: Option<String>,
     enabled: bool,
+    tags: Vec<String>,
+    seed: u32,
+    hash_algorithm: HashAlgorithm,
+    hash_key_format: HashKeyFormat,
+    salt: String,
+    bucketing_key: Option<String>,
     value_type: ValueType,
+    element_type: Option<ValueType>,
     variants: HashMap<String, Value>,
+    variant_metadata: HashMap<String, BTreeMap<String, String>>,
     default_variant: Option<String>,
+    off_variant: Option<String>,
+    fallback_variant: Option<String>,
+    prerequisites: Vec<(String, String)>,
     rules: Vec<Rule>,
     default_rule: Option<Rule>,
+    default_dimension: Option<String>,
+    dimension_rules: HashMap<String, Rule>,
+    require_default: bool,
 }
 
 impl FeatureBuilder {
@@ -29,11 +45,25 @@ impl FeatureBuilder {
         Self {
             name: None,
             enabled: false,
+            tags: Vec::new(),
+            seed: 0,
+            hash_algorithm: HashAlgorithm::default(),
+            hash_key_format: HashKeyFormat::default(),
+            salt: String::new(),
+            bucketing_key: None,
             value_type,
+            element_type: None,
             variants: HashMap::new(),
+            variant_metadata: HashMap::new(),
             default_variant: None,
+            off_variant: None,
+            fallback_variant: None,
+            prerequisites: Vec::new(),
             rules: Vec::new(),
             default_rule: None,
+            default_dimension: None,
+            dimension_rules: HashMap::new(),
+            require_default: true,
         }
     }
 
@@ -49,32 +79,256 @@ impl FeatureBuilder {
         self
     }
 
+    /// Adds a tag to the feature, which can be used to evaluate groups of features.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Sets the seed used when hashing the user key.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the algorithm used when hashing the user key.
+    pub fn hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = hash_algorithm;
+        self
+    }
+
+    /// Sets how the feature and user key are combined before hashing.
+    pub fn hash_key_format(mut self, hash_key_format: HashKeyFormat) -> Self {
+        self.hash_key_format = hash_key_format;
+        self
+    }
+
+    /// Sets the salt that is mixed into the hash to decorrelate bucketing between features.
+    pub fn salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Sets the context attribute used for bucketing in place of the user key.
+    ///
+    /// Contexts without a string value for the attribute are bucketed by the user key, and the decision carries a warning.
+    pub fn bucketing_key(mut self, attribute: impl Into<String>) -> Self {
+        self.bucketing_key = Some(attribute.into());
+        self
+    }
+
+    /// Sets the element type for array features.
+    pub fn element_type(mut self, element_type: ValueType) -> Self {
+        self.element_type = Some(element_type);
+        self
+    }
+
     /// Adds a variant with the specified key and value to the feature.
     pub fn variant(mut self, key: impl Into<String>, value: Value) -> Self {
         self.variants.insert(key.into(), value);
         self
     }
 
+    /// Adds a variant with the specified key and value to the feature, returning an error immediately if the value does
+    /// not have the value type of the feature, rather than when the feature is built.
+    pub fn variant_with_type_check(
+        self,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Result<Self, FetaError> {
+        let key = key.into();
+        if !value.has_type(&self.value_type) {
+            return Err(FetaError::Configuration(format!(
+                "variant {} must have type: {}",
+                key, self.value_type
+            )));
+        }
+
+        Ok(self.variant(key, value))
+    }
+
+    /// Adds all of the specified variants to the feature.
+    pub fn variants(self, variants: impl IntoIterator<Item = (impl Into<String>, Value)>) -> Self {
+        variants
+            .into_iter()
+            .fold(self, |builder, (key, value)| builder.variant(key, value))
+    }
+
+    /// Sets the metadata for the specified variant, such as a display name or analytics ID, which is returned with its decisions.
+    pub fn variant_metadata(
+        mut self,
+        key: impl Into<String>,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        let metadata = metadata
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+
+        self.variant_metadata.insert(key.into(), metadata);
+        self
+    }
+
     /// Sets the default variant for the feature.
     pub fn default_variant(mut self, key: impl Into<String>) -> Self {
         self.default_variant = Some(key.into());
         self
     }
 
+    /// Sets the variant returned when the feature is disabled, which defaults to the default variant.
+    pub fn off_variant(mut self, key: impl Into<String>) -> Self {
+        self.off_variant = Some(key.into());
+        self
+    }
+
+    /// Sets the variant returned when evaluation fails, which defaults to the default variant.
+    pub fn fallback_variant(mut self, key: impl Into<String>) -> Self {
+        self.fallback_variant = Some(key.into());
+        self
+    }
+
+    /// Adds a prerequisite, which requires the specified feature to evaluate to the specified variant.
+    ///
+    /// Prerequisites are evaluated by `Features`, as they require access to sibling features.
+    pub fn prerequisite(mut self, feature: impl Into<String>, variant: impl Into<String>) -> Self {
+        self.prerequisites.push((feature.into(), variant.into()));
+        self
+    }
+
     /// Sets the default rule for the feature.
     pub fn default_rule(mut self, rule: Rule) -> Self {
         self.default_rule = Some(rule);
         self
     }
 
-    /// Adds an audience rule to the feature.
+    /// Sets the context attribute whose value selects a dimension rule in place of the default rule, such as `region`.
+    pub fn default_dimension(mut self, attribute: impl Into<String>) -> Self {
+        self.default_dimension = Some(attribute.into());
+        self
+    }
+
+    /// Adds a default rule that is applied in place of the default rule when the dimension attribute has the specified value.
+    pub fn dimension_rule(mut self, value: impl Into<String>, rule: Rule) -> Self {
+        self.dimension_rules.insert(value.into(), rule);
+        self
+    }
+
+    /// Sets whether a default rule is required, which defaults to `true`.
+    ///
+    /// If `false`, the default rule may be omitted and the default variant is returned with `Reason::NoMatch` when no audience rules apply.
+    pub fn require_default(mut self, require_default: bool) -> Self {
+        self.require_default = require_default;
+        self
+    }
+
+    /// Adds an audience rule to the feature, which is evaluated in priority order.
     pub fn audience_rule(mut self, rule: Rule) -> Self {
         self.rules.push(rule);
         self
     }
 
+    /// Creates a `FeatureBuilder` from the given name and configuration.
+    ///
+    /// Boolean features that omit variants use `on` and `off` variants, with `off` as the default unless specified.
+    pub fn from_config(name: &str, cfg: &config::Feature) -> Result<Self, FetaError> {
+        Self::from_config_with_functions(name, cfg, &Functions::default())
+    }
+
+    /// Creates a `FeatureBuilder` from the given name and configuration, with host functions that audience expressions can call.
+    pub fn from_config_with_functions(
+        name: &str,
+        cfg: &config::Feature,
+        functions: &Functions,
+    ) -> Result<Self, FetaError> {
+        let shorthand = cfg.value_type == ValueType::Boolean && cfg.variants.is_empty();
+        let default_variant = match cfg.default_variant.as_str() {
+            "" if shorthand => "off",
+            v => v,
+        };
+
+        let mut builder = FeatureBuilder::new(cfg.value_type)
+            .name(name)
+            .enabled(cfg.enabled)
+            .salt(&cfg.salt)
+            .default_variant(default_variant)
+            .require_default(cfg.require_default);
+
+        if shorthand {
+            builder = builder
+                .variant("on", Value::Boolean(true))
+                .variant("off", Value::Boolean(false));
+        }
+
+        if let Some(default_rule) = &cfg.default_rule {
+            builder = builder.default_rule(
+                new_rule_builder(&default_rule.bucketing)
+                    .normalize(default_rule.normalize)
+                    .as_default()
+                    .build()?,
+            );
+        }
+
+        if let Some(default_dimension) = &cfg.default_dimension {
+            builder = builder.default_dimension(default_dimension);
+        }
+
+        for (value, rule) in &cfg.dimension_rules {
+            builder = builder.dimension_rule(
+                value,
+                new_rule_builder(&rule.bucketing)
+                    .normalize(rule.normalize)
+                    .as_default()
+                    .build()?,
+            );
+        }
+
+        if let Some(bucketing_key) = &cfg.bucketing_key {
+            builder = builder.bucketing_key(bucketing_key);
+        }
+
+        if let Some(off_variant) = &cfg.off_variant {
+            builder = builder.off_variant(off_variant);
+        }
+
+        if let Some(fallback_variant) = &cfg.fallback_variant {
+            builder = builder.fallback_variant(fallback_variant);
+        }
+
+        if let Some(element_type) = cfg.element_type {
+            builder = builder.element_type(element_type);
+        }
+
+        for tag in &cfg.tags {
+            builder = builder.tag(tag);
+        }
+
+        for (variant, value) in &cfg.variants {
+            builder = builder.variant(variant, value.clone());
+        }
+
+        for (variant, metadata) in &cfg.variant_metadata {
+            builder = builder.variant_metadata(variant, metadata.clone());
+        }
+
+        for prerequisite in &cfg.prerequisites {
+            builder = builder.prerequisite(&prerequisite.feature, &prerequisite.variant);
+        }
+
+        for rule in &cfg.audience_rules {
+            builder = builder.audience_rule(new_audience_rule(rule, functions)?)
+        }
+
+        Ok(builder)
+    }
+
     /// Builds the `Feature` instance with the current values.
     pub fn build(mut self) -> Result<Feature, FetaError> {
+        if self.variants.contains_key("") {
+            return Err(FetaError::Configuration(
+                "variant name must not be empty".to_string(),
+            ));
+        }
+
         for value in self.variants.values() {
             if !value.has_type(&self.value_type) {
                 return Err(FetaError::Configuration(format!(
@@ -82,6 +336,27 @@ impl FeatureBuilder {
                     self.value_type
                 )));
             }
+            // a non-finite value never equals itself, so it cannot be compared or meaningfully served
+            if !value.is_finite() {
+                return Err(FetaError::Configuration(
+                    "variant values must be finite".to_string(),
+                ));
+            }
+        }
+
+        if self.value_type == ValueType::Array {
+            let element_type = self.element_type.ok_or(FetaError::Configuration(
+                "element type is required for array features".to_string(),
+            ))?;
+
+            for value in self.variants.values() {
+                if !value.has_element_type(&element_type) {
+                    return Err(FetaError::Configuration(format!(
+                        "all array elements must have type: {}",
+                        element_type
+                    )));
+                }
+            }
         }
 
         let default_variant = self.default_variant.ok_or(FetaError::Configuration(
@@ -98,18 +373,75 @@ impl FeatureBuilder {
             }
         };
 
-        let default_rule = self.default_rule.ok_or(FetaError::Configuration(
-            "default rule is required".to_string(),
-        ))?;
-        if default_rule.program.is_some() {
+        // variant types are validated above, so existing off and fallback variants match the value type
+        let off_variant = self.off_variant.unwrap_or(default_variant.clone());
+        let off_value = match self.variants.get(&off_variant) {
+            Some(v) => v.clone(),
+            None => {
+                return Err(FetaError::Configuration(format!(
+                    "off variant does not exist: {}",
+                    off_variant
+                )));
+            }
+        };
+
+        let fallback_variant = self.fallback_variant.unwrap_or(default_variant.clone());
+        let fallback_value = match self.variants.get(&fallback_variant) {
+            Some(v) => v.clone(),
+            None => {
+                return Err(FetaError::Configuration(format!(
+                    "fallback variant does not exist: {}",
+                    fallback_variant
+                )));
+            }
+        };
+
+        if let Some(variant) = self
+            .variant_metadata
+            .keys()
+            .find(|v| !self.variants.contains_key(*v))
+        {
+            return Err(FetaError::Configuration(format!(
+                "variant metadata for undefined variant: {}",
+                variant
+            )));
+        }
+
+        if self.default_rule.is_none() && self.require_default {
             return Err(FetaError::Configuration(
-                "default rule must not have an expression".to_string(),
+                "default rule is required".to_string(),
             ));
         }
 
-        self.rules.push(default_rule);
+        if !self.dimension_rules.is_empty() {
+            if self.default_dimension.is_none() {
+                return Err(FetaError::Configuration(
+                    "default dimension is required for dimension rules".to_string(),
+                ));
+            }
+            if self.default_rule.is_none() {
+                return Err(FetaError::Configuration(
+                    "default rule is required for dimension rules".to_string(),
+                ));
+            }
+        }
+
+        // sort is stable, so rules with equal priority are evaluated in the order they were added
+        self.rules.sort_by_key(|r| r.priority);
+
+        // default rules are not required to be built with `as_default`, so are validated here as well
+        if let Some(mut default_rule) = self.default_rule {
+            validate_default_rule(&default_rule)?;
+            default_rule.is_default = true;
+            self.rules.push(default_rule);
+        }
+
+        for rule in self.dimension_rules.values_mut() {
+            validate_default_rule(rule)?;
+            rule.is_default = true;
+        }
 
-        for rule in self.rules.iter() {
+        for rule in self.rules.iter().chain(self.dimension_rules.values()) {
             for variant in rule.referenced_variants() {
                 if !self.variants.contains_key(variant) {
                     return Err(FetaError::Configuration(format!(
@@ -120,109 +452,529 @@ impl FeatureBuilder {
             }
         }
 
+        let name = self.name.ok_or(FetaError::Configuration(
+            "feature name is required".to_string(),
+        ))?;
+
+        if name.is_empty() {
+            return Err(FetaError::Configuration(
+                "feature name must not be empty".to_string(),
+            ));
+        }
+
         Ok(Feature {
-            name: self.name.ok_or(FetaError::Configuration(
-                "feature name is required".to_string(),
-            ))?,
+            hash_key: format!("{}{}", name, self.salt),
+            name,
+            salt: self.salt,
+            value_type: self.value_type,
+            element_type: self.element_type,
             enabled: self.enabled,
+            tags: self.tags,
+            seed: self.seed,
+            hash_algorithm: self.hash_algorithm,
+            hash_key_format: self.hash_key_format,
+            bucketing_key: self.bucketing_key,
             variants: self.variants,
+            variant_metadata: self.variant_metadata,
             default_variant,
             default_value,
+            off_variant,
+            off_value,
+            fallback_variant,
+            fallback_value,
+            prerequisites: self.prerequisites,
             rules: self.rules,
+            default_dimension: self.default_dimension,
+            dimension_rules: self.dimension_rules,
+            require_default: self.require_default,
         })
     }
 }
 
+/// Validates that the rule can be used as a default rule, which applies to every context.
+fn validate_default_rule(rule: &Rule) -> Result<(), FetaError> {
+    if rule.has_expression() {
+        return Err(FetaError::Configuration(
+            "default rule must not have an expression".to_string(),
+        ));
+    }
+    if rule.is_scheduled() {
+        return Err(FetaError::Configuration(
+            "default rule must not be scheduled".to_string(),
+        ));
+    }
+    if rule.is_partial() {
+        return Err(FetaError::Configuration(
+            "default rule percentages must total 100".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Creates a `RuleBuilder` from the given bucketing configuration.
 fn new_rule_builder(bucketing: &config::Bucketing) -> RuleBuilder {
-    let mut builder = RuleBuilder::new();
     match bucketing {
-        config::Bucketing::Variant { variant } => builder = builder.variant(variant.clone(), 100),
+        config::Bucketing::Variant { variant } => RuleBuilder::new().variant(variant, 100),
+        config::Bucketing::Rollout {
+            variant,
+            percentage,
+        } => RuleBuilder::new().variant(variant, *percentage),
         config::Bucketing::Distribution { distribution } => {
-            for (variant, percentage) in distribution {
-                builder = builder.variant(variant, *percentage)
-            }
+            RuleBuilder::new().distribution(distribution.iter().map(|(k, p)| (k, *p)))
         }
+    }
+}
+
+/// Creates an audience `Rule` from the given configuration.
+fn new_audience_rule(cfg: &config::AudienceRule, functions: &Functions) -> Result<Rule, FetaError> {
+    let builder = new_rule_builder(&cfg.bucketing)
+        .normalize(cfg.normalize)
+        .functions(functions.clone());
+    let mut builder = match &cfg.expression {
+        config::Expression::Single { expression } => builder.audience(&cfg.name, expression),
+        config::Expression::All { all } => builder.audience_all(&cfg.name, all),
+        config::Expression::Any { any } => builder.audience_any(&cfg.name, any),
+    }
+    .negate(cfg.negate)
+    .priority(cfg.priority);
+
+    if let Some(starts_at) = &cfg.starts_at {
+        builder = builder.starts_at(starts_at);
+    }
+
+    if let Some(ends_at) = &cfg.ends_at {
+        builder = builder.ends_at(ends_at);
+    }
+
+    builder.build()
+}
+
+/// Creates a `Feature` from the given name and configuration, returning all errors rather than only the first.
+pub(crate) fn from_config_collecting_errors(
+    name: &str,
+    cfg: &config::Feature,
+) -> Result<Feature, Vec<FetaError>> {
+    // rules are validated individually so that every invalid rule is reported
+    let mut errors: Vec<FetaError> = cfg
+        .audience_rules
+        .iter()
+        .filter_map(|r| {
+            new_audience_rule(r, &Functions::default())
+                .err()
+                .map(|e| e.with_context(&format!("rule {}", r.name)))
+        })
+        .collect();
+
+    if let Some(Err(e)) = cfg.default_rule.as_ref().map(|r| {
+        new_rule_builder(&r.bucketing)
+            .normalize(r.normalize)
+            .as_default()
+            .build()
+    }) {
+        errors.push(e.with_context("default rule"));
+    }
+
+    errors.extend(cfg.dimension_rules.iter().filter_map(|(value, r)| {
+        new_rule_builder(&r.bucketing)
+            .normalize(r.normalize)
+            .as_default()
+            .build()
+            .err()
+            .map(|e| e.with_context(&format!("dimension rule {}", value)))
+    }));
+
+    // the feature can only be built once all rules are valid, which covers the remaining validation
+    let result = if errors.is_empty() {
+        FeatureBuilder::from_config(name, cfg)
+            .and_then(|b| b.build())
+            .map_err(|e| vec![e])
+    } else {
+        Err(errors)
     };
-    builder
+
+    result.map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|e| e.with_context(&format!("feature {}", name)))
+            .collect()
+    })
+}
+
+/// Read-only metadata describing a feature, for tooling that displays features without evaluating them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureInfo {
+    pub name: String,
+    pub enabled: bool,
+    pub value_type: ValueType,
+    /// The variant keys for the feature, sorted alphabetically.
+    pub variants: Vec<String>,
+    pub default_variant: String,
 }
 
 /// The `Feature` struct represents a feature with its configuration and rules for evaluation.
+#[derive(Debug, Clone)]
 pub struct Feature {
     name: String,
+    salt: String,
+    hash_key: String,
+    value_type: ValueType,
+    element_type: Option<ValueType>,
     enabled: bool,
+    tags: Vec<String>,
+    seed: u32,
+    hash_algorithm: HashAlgorithm,
+    hash_key_format: HashKeyFormat,
+    bucketing_key: Option<String>,
     variants: HashMap<String, Value>,
+    variant_metadata: HashMap<String, BTreeMap<String, String>>,
     default_variant: String,
     default_value: Value,
+    off_variant: String,
+    off_value: Value,
+    fallback_variant: String,
+    fallback_value: Value,
+    prerequisites: Vec<(String, String)>,
     rules: Vec<Rule>,
+    default_dimension: Option<String>,
+    dimension_rules: HashMap<String, Rule>,
+    require_default: bool,
 }
 
 impl Feature {
     /// Creates a `Feature` instance from the given name and configuration.
     pub fn from_config(name: &str, cfg: &config::Feature) -> Result<Self, FetaError> {
-        let mut builder = FeatureBuilder::new(cfg.value_type)
-            .name(name)
-            .enabled(cfg.enabled)
-            .default_variant(cfg.default_variant.clone())
-            .default_rule(new_rule_builder(&cfg.default_rule.bucketing).build()?);
+        FeatureBuilder::from_config(name, cfg)?.build()
+    }
 
-        for (variant, value) in &cfg.variants {
-            builder = builder.variant(variant, value.clone());
-        }
+    /// Reconstructs the configuration for the feature.
+    pub fn to_config(&self) -> config::Feature {
+        // the default rule is always the last rule if it exists, and audience rules are sorted by priority
+        let (default_rule, audience_rules) = match self.rules.split_last() {
+            Some((last, rest)) if last.is_default => (Some(last), rest),
+            _ => (None, self.rules.as_slice()),
+        };
 
-        for rule in &cfg.audience_rules {
-            builder = builder.audience_rule(
-                new_rule_builder(&rule.bucketing)
-                    .audience(&rule.name, &rule.expression)
-                    .build()?,
-            )
+        let optional_variant =
+            |variant: &String| (*variant != self.default_variant).then(|| variant.clone());
+
+        config::Feature {
+            enabled: self.enabled,
+            tags: self.tags.clone(),
+            salt: self.salt.clone(),
+            bucketing_key: self.bucketing_key.clone(),
+            value_type: self.value_type,
+            element_type: self.element_type,
+            variants: self
+                .variants
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            variant_metadata: self
+                .variant_metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            default_variant: self.default_variant.clone(),
+            off_variant: optional_variant(&self.off_variant),
+            fallback_variant: optional_variant(&self.fallback_variant),
+            prerequisites: self
+                .prerequisites
+                .iter()
+                .map(|(feature, variant)| config::Prerequisite {
+                    feature: feature.clone(),
+                    variant: variant.clone(),
+                })
+                .collect(),
+            audience_rules: audience_rules
+                .iter()
+                .filter_map(|r| r.to_audience_rule())
+                .collect(),
+            default_rule: default_rule.map(|r| config::DefaultRule {
+                bucketing: r.to_bucketing(),
+                normalize: false,
+            }),
+            default_dimension: self.default_dimension.clone(),
+            dimension_rules: self
+                .dimension_rules
+                .iter()
+                .map(|(value, r)| {
+                    let rule = config::DefaultRule {
+                        bucketing: r.to_bucketing(),
+                        normalize: false,
+                    };
+                    (value.clone(), rule)
+                })
+                .collect(),
+            require_default: self.require_default,
         }
+    }
+
+    /// Returns the tags for the feature.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Returns the name of the feature.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the feature is enabled.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the declared value type of the feature.
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Returns an iterator over the variant keys for the feature, in no particular order.
+    pub fn variant_keys(&self) -> impl Iterator<Item = &str> {
+        self.variants.keys().map(|k| k.as_str())
+    }
+
+    /// Returns the default variant for the feature.
+    pub fn default_variant(&self) -> &str {
+        &self.default_variant
+    }
 
-        builder.build()
+    /// Returns read-only metadata describing the feature, without evaluating it.
+    pub fn info(&self) -> FeatureInfo {
+        let mut variants: Vec<String> = self.variant_keys().map(String::from).collect();
+        variants.sort();
+
+        FeatureInfo {
+            name: self.name.clone(),
+            enabled: self.enabled,
+            value_type: self.value_type,
+            variants,
+            default_variant: self.default_variant.clone(),
+        }
     }
 
     /// Evaluates the feature for the given context and returns a `Decision` with the result.
+    ///
+    /// Prerequisites are not evaluated, as they require access to sibling features; use `Features::decide` instead.
     pub fn decide(&self, ctx: &Context) -> Decision {
-        let mut builder = DecisionBuilder::new()
-            .variant(&self.default_variant)
-            .value(self.default_value.clone());
+        self.decide_with_env(ctx, &ctx.environment())
+    }
 
-        let hash = hash::calculate(&self.name, &ctx.user_key);
-        builder = builder.hash(hash);
+    /// Evaluates the feature for the given context using an `Environment` created with `Context::environment`.
+    pub fn decide_with_env(&self, ctx: &Context, env: &Environment) -> Decision {
+        self.decide_with_prerequisites(ctx, env, false, |_, _| true)
+    }
 
+    /// Evaluates the feature for the given context, using the specified function to determine whether each prerequisite feature and variant is met.
+    ///
+    /// If `excluded` is `true`, the context was assigned to another feature in the same exclusion group, so the default variant is returned.
+    pub(crate) fn decide_with_prerequisites(
+        &self,
+        ctx: &Context,
+        env: &Environment,
+        excluded: bool,
+        is_met: impl Fn(&str, &str) -> bool,
+    ) -> Decision {
+        let mut decision = self.decide_variant(ctx, env, excluded, is_met);
+        decision.metadata = self.variant_metadata.get(&decision.variant).cloned();
+        decision
+    }
+
+    /// Evaluates the variant for the given context, as described by `decide_with_prerequisites`.
+    fn decide_variant(
+        &self,
+        ctx: &Context,
+        env: &Environment,
+        excluded: bool,
+        is_met: impl Fn(&str, &str) -> bool,
+    ) -> Decision {
+        // the variant and value are set once on each return path, so the default value is only cloned when served
+        let mut builder = DecisionBuilder::new()
+            .value_type(self.value_type)
+            .anonymous(ctx.anonymous);
+
+        // disabled features are not bucketed, so the hash is skipped and left as zero
         if !self.enabled {
-            return builder.disabled();
+            return builder
+                .variant(&self.off_variant)
+                .value(self.off_value.clone())
+                .disabled();
         }
 
-        let mut env = Environment::default();
-        if let Some(attributes) = &ctx.attributes {
-            for (key, value) in attributes {
-                env.set(key, value.clone());
+        // anonymous contexts are bucketed with a random key, so assignments are not stable
+        let ephemeral_key = ctx.anonymous.then(hash::ephemeral_key);
+        let user_key = ephemeral_key.as_deref().unwrap_or(&ctx.user_key);
+
+        let bucketing_value = match self.bucketing_value(ctx, user_key) {
+            Ok(v) => v,
+            Err(e) => {
+                builder = builder.warning(e);
+                user_key
             }
+        };
+
+        let hash = hash::calculate_with_options(
+            self.hash_algorithm,
+            self.hash_key_format,
+            &self.hash_key,
+            bucketing_value,
+            self.seed,
+        );
+        builder = builder.hash(hash);
+
+        if let Some(variant) = ctx.overrides.as_ref().and_then(|o| o.get(&self.name)) {
+            return match self.variant_value(variant) {
+                Ok(v) => builder.variant(variant).value(v).success(Reason::Override),
+                Err(e) => self.fail(builder, e),
+            };
+        }
+
+        if excluded {
+            return self.serve_default(builder, Reason::Excluded);
         }
 
-        for rule in &self.rules {
-            let applicable = match rule.is_applicable(&env) {
+        if !self
+            .prerequisites
+            .iter()
+            .all(|(feature, variant)| is_met(feature, variant))
+        {
+            return self.serve_default(builder, Reason::Prerequisite);
+        }
+
+        let mut now = None;
+        for (index, rule) in self.rules.iter().enumerate() {
+            // the default rule is replaced by the dimension rule for the context, if there is one
+            let rule = if rule.is_default {
+                self.dimension_rule(ctx).unwrap_or(rule)
+            } else {
+                rule
+            };
+
+            // the current time is parsed once and only for scheduled rules, so an invalid value does not affect other rules
+            let rule_now = match (rule.is_scheduled(), now) {
+                (false, _) => None,
+                (true, Some(n)) => n,
+                (true, None) => match self.now(ctx) {
+                    Ok(n) => *now.insert(n),
+                    Err(e) => return self.fail(builder, e),
+                },
+            };
+
+            let applicable = match rule.is_applicable_in(ctx, env, rule_now) {
                 Ok(b) => b,
-                Err(e) => return builder.error(e),
+                Err(e) => {
+                    let context = format!(
+                        "in feature '{}' audience '{}'",
+                        self.name,
+                        rule.audience().unwrap_or_default()
+                    );
+                    return self.fail(builder, e.with_context(&context));
+                }
             };
 
             if applicable {
-                let variant = &rule.get_variant(hash);
+                // partial rules fall through to the next rule if the hash is not bucketed
+                let Some(variant) = rule.get_variant(hash) else {
+                    continue;
+                };
+
                 if let Some(audience) = &rule.audience {
                     builder = builder.audience(audience);
                 }
 
                 match self.variant_value(variant) {
-                    Ok(v) => return builder.variant(variant).value(v).success(rule.reason),
-                    Err(e) => return builder.error(e),
+                    Ok(v) => {
+                        return builder
+                            .variant(variant)
+                            .value(v)
+                            .is_default(rule.is_default)
+                            .rule_index(index)
+                            .success(rule.reason);
+                    }
+                    Err(e) => return self.fail(builder, e),
                 }
             }
         }
 
-        builder.error(FetaError::Configuration(
-            "no applicable rules defined".to_string(),
-        ))
+        if !self.require_default {
+            return self.serve_default(builder, Reason::NoMatch);
+        }
+
+        self.fail(
+            builder,
+            FetaError::Configuration("no applicable rules defined".to_string()),
+        )
+    }
+
+    /// Builds the decision as successful with the specified reason, with the default variant and value.
+    fn serve_default(&self, builder: DecisionBuilder, reason: Reason) -> Decision {
+        builder
+            .variant(&self.default_variant)
+            .value(self.default_value.clone())
+            .success(reason)
+    }
+
+    /// Builds the decision as an error, with the fallback variant and value.
+    fn fail(&self, builder: DecisionBuilder, err: FetaError) -> Decision {
+        builder
+            .variant(&self.fallback_variant)
+            .value(self.fallback_value.clone())
+            .error(err)
+    }
+
+    /// Returns the dimension rule selected by the value of the dimension attribute, if it is a string with a rule.
+    fn dimension_rule(&self, ctx: &Context) -> Option<&Rule> {
+        match ctx.attribute(self.default_dimension.as_ref()?) {
+            Some(Object::String(value)) => self.dimension_rules.get(value),
+            _ => None,
+        }
+    }
+
+    /// Retrieves the value used for bucketing, returning an error if the bucketing attribute is not a string.
+    fn bucketing_value<'a>(
+        &self,
+        ctx: &'a Context,
+        user_key: &'a str,
+    ) -> Result<&'a str, FetaError> {
+        let Some(key) = &self.bucketing_key else {
+            return Ok(user_key);
+        };
+
+        match ctx.attribute(key) {
+            Some(Object::String(s)) => Ok(s),
+            _ => Err(FetaError::Request(format!(
+                "bucketing attribute must be a string: {}",
+                key
+            ))),
+        }
+    }
+
+    /// Retrieves the current time from the context, returning an error if the attribute is not a valid RFC3339 timestamp.
+    fn now(&self, ctx: &Context) -> Result<Option<DateTime<FixedOffset>>, FetaError> {
+        let Some(value) = ctx.attribute(NOW_ATTRIBUTE) else {
+            return Ok(None);
+        };
+
+        match value {
+            Object::String(s) => DateTime::parse_from_rfc3339(s).ok(),
+            _ => None,
+        }
+        .map(Some)
+        .ok_or(FetaError::Request(format!(
+            "{} attribute must be an RFC3339 timestamp",
+            NOW_ATTRIBUTE
+        )))
+    }
+
+    /// Returns the prerequisite feature and variant pairs for the feature.
+    pub(crate) fn prerequisites(&self) -> &[(String, String)] {
+        &self.prerequisites
+    }
+
+    /// Returns `true` if the feature defines the specified variant.
+    pub(crate) fn has_variant(&self, variant: &str) -> bool {
+        self.variants.contains_key(variant)
     }
 
     /// Retrieves the value for the specified variant, returning an error if the variant is not defined.
@@ -239,9 +991,9 @@ impl Feature {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
+    use std::collections::HashSet;
 
-    use crate::{RuleBuilder, decision::Reason};
+    use crate::{ContextBuilder, RuleBuilder};
 
     use super::*;
 
@@ -265,16 +1017,149 @@ mod tests {
     }
 
     #[test]
-    fn test_feature_builder_errors() {
-        let tests = vec![
-            FeatureBuilder::new(ValueType::Integer) // no name
-                .variant("a", 1.into())
-                .enabled(true)
-                .default_variant("a")
-                .default_rule(
-                    RuleBuilder::new()
-                        .variant("a", 100)
-                        .build()
+    fn test_feature_builder_bulk() {
+        let keys = ["a", "b", "c", "d", "e"];
+
+        let rule = RuleBuilder::new()
+            .distribution(keys.map(|k| (k, 20)))
+            .build()
+            .expect("rule should build");
+
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("feature")
+            .enabled(true)
+            .variants(keys.iter().zip(1..).map(|(k, v)| (*k, Value::from(v))))
+            .default_variant("a")
+            .default_rule(rule)
+            .build()
+            .expect("feature should build");
+
+        let mut actual: Vec<&str> = feature.variant_keys().collect();
+        actual.sort();
+        assert_eq!(actual, keys);
+
+        let actual: Vec<u32> = feature.rules[0]
+            .bucket_ranges()
+            .into_iter()
+            .map(|(_, lower, _)| lower)
+            .collect();
+        assert_eq!(actual, vec![0, 2000, 4000, 6000, 8000]);
+    }
+
+    #[test]
+    fn test_feature_builder_object() {
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .build()
+            .expect("rule should build");
+
+        let value = Value::Object(BTreeMap::from([
+            ("timeout_ms".to_string(), 500.into()),
+            ("retries".to_string(), 3.into()),
+        ]));
+
+        let feature = FeatureBuilder::new(ValueType::Object)
+            .name("feature")
+            .enabled(true)
+            .variant("a", value.clone())
+            .default_variant("a")
+            .default_rule(rule)
+            .build()
+            .expect("feature should build");
+
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.value, value);
+    }
+
+    #[test]
+    fn test_feature_builder_array() {
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .build()
+            .expect("rule should build");
+
+        let value = Value::Array(vec!["plugin_a".into(), "plugin_b".into()]);
+
+        let feature = FeatureBuilder::new(ValueType::Array)
+            .name("feature")
+            .enabled(true)
+            .element_type(ValueType::String)
+            .variant("a", value.clone())
+            .variant("b", Value::Array(Vec::new()))
+            .default_variant("a")
+            .default_rule(rule)
+            .build()
+            .expect("feature should build");
+
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.value, value);
+    }
+
+    #[test]
+    fn test_feature_builder_variant_with_type_check() {
+        let builder = FeatureBuilder::new(ValueType::Integer)
+            .variant_with_type_check("a", 1.into())
+            .expect("variant should be added");
+
+        let Err(err) =
+            FeatureBuilder::new(ValueType::Integer).variant_with_type_check("b", "abc".into())
+        else {
+            panic!("variant should be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: variant b must have type: integer"
+        );
+
+        let feature = builder
+            .name("f1")
+            .enabled(true)
+            .default_variant("a")
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+        assert_eq!(feature.decide(&Context::new("key")).value, 1.into());
+    }
+
+    #[test]
+    fn test_feature_builder_errors() {
+        let tests = vec![
+            FeatureBuilder::new(ValueType::Integer) // no name
+                .variant("a", 1.into())
+                .enabled(true)
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Integer) // empty name
+                .name("")
+                .variant("a", 1.into())
+                .enabled(true)
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Integer) // empty variant name
+                .name("f1")
+                .enabled(true)
+                .variant("a", 1.into())
+                .variant("", 2.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
                         .expect("rule should build"),
                 ),
             FeatureBuilder::new(ValueType::Integer) // no default rule
@@ -300,6 +1185,41 @@ mod tests {
                         .build()
                         .expect("rule should build"),
                 ),
+            FeatureBuilder::new(ValueType::Integer) // undefined fallback variant
+                .name("f1")
+                .enabled(true)
+                .variant("a", 1.into())
+                .default_variant("a")
+                .fallback_variant("fallback")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Integer) // undefined off variant
+                .name("f1")
+                .enabled(false)
+                .variant("a", 1.into())
+                .default_variant("a")
+                .off_variant("off")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Integer) // partial default rule
+                .name("f1")
+                .enabled(true)
+                .variant("a", 1.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 50)
+                        .build()
+                        .expect("rule should build"),
+                ),
             FeatureBuilder::new(ValueType::Integer) // no default variant
                 .name("f1")
                 .enabled(true)
@@ -345,6 +1265,42 @@ mod tests {
                         .build()
                         .expect("rule should build"),
                 ),
+            FeatureBuilder::new(ValueType::Float) // nan variant value
+                .name("f1")
+                .enabled(true)
+                .variant("a", f64::NAN.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Array) // no element type
+                .name("f1")
+                .enabled(true)
+                .variant("a", vec!["x".into()].into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                ),
+            FeatureBuilder::new(ValueType::Array) // element type mismatch
+                .name("f1")
+                .enabled(true)
+                .element_type(ValueType::String)
+                .variant("a", vec!["x".into()].into())
+                .variant("b", vec!["y".into(), 1.into()].into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 50)
+                        .variant("b", 50)
+                        .build()
+                        .expect("rule should build"),
+                ),
         ];
 
         for test in tests {
@@ -353,29 +1309,748 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_feature_builder_default_variant_type() {
+        let Err(err) = FeatureBuilder::new(ValueType::Boolean)
+            .name("f1")
+            .enabled(true)
+            .variant("a", true.into())
+            .variant("b", 1.into())
+            .default_variant("b")
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+        else {
+            panic!("feature should be rejected");
+        };
+
+        // the default variant is rejected by the check that applies to all variants
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: all variants must have type: boolean"
+        );
+    }
+
     #[test]
     fn test_feature_from_config() {
         let config = config::Feature {
             enabled: true,
+            tags: vec!["checkout".to_string()],
             value_type: ValueType::Integer,
+            element_type: None,
+            salt: String::new(),
+            bucketing_key: None,
             variants: BTreeMap::from([("a".to_string(), 1.into()), ("b".to_string(), 2.into())]),
+            variant_metadata: BTreeMap::from([(
+                "b".to_string(),
+                BTreeMap::from([("analytics_id".to_string(), "exp-b".to_string())]),
+            )]),
             default_variant: "a".to_string(),
-            default_rule: config::DefaultRule {
+            off_variant: None,
+            fallback_variant: None,
+            prerequisites: vec![],
+            default_rule: Some(config::DefaultRule {
                 bucketing: config::Bucketing::Distribution {
-                    distribution: BTreeMap::from([("a".to_string(), 50), ("b".to_string(), 50)]),
+                    distribution: BTreeMap::from([
+                        ("a".to_string(), 50.0),
+                        ("b".to_string(), 50.0),
+                    ]),
                 },
-            },
+                normalize: false,
+            }),
+            default_dimension: None,
+            dimension_rules: BTreeMap::new(),
+            require_default: true,
             audience_rules: vec![config::AudienceRule {
                 name: "beta".to_string(),
-                expression: "beta".to_string(),
+                expression: config::Expression::Single {
+                    expression: "beta".to_string(),
+                },
+                negate: false,
+                priority: 0,
+                starts_at: None,
+                ends_at: None,
                 bucketing: config::Bucketing::Variant {
                     variant: "b".to_string(),
                 },
+                normalize: false,
             }],
         };
 
-        let feature = Feature::from_config("exp", &config);
-        assert!(!feature.is_err())
+        let feature = Feature::from_config("exp", &config).expect("feature should build");
+        assert_eq!(feature.tags(), ["checkout".to_string()]);
+        assert_eq!(feature.to_config(), config);
+
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .bool_attribute("beta", true)
+            .build()
+            .expect("context should build");
+        let actual = feature.decide(&ctx);
+        assert_eq!(actual.variant, "b");
+        assert_eq!(
+            actual.metadata.and_then(|m| m.get("analytics_id").cloned()),
+            Some("exp-b".to_string())
+        );
+
+        // only variant b has metadata, and the default rule serves either variant
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.metadata.is_some(), actual.variant == "b");
+
+        let config = config::Feature {
+            variant_metadata: BTreeMap::from([("c".to_string(), BTreeMap::new())]),
+            ..config
+        };
+        assert!(Feature::from_config("exp", &config).is_err());
+    }
+
+    #[test]
+    fn test_feature_from_config_boolean_shorthand() {
+        let config: config::Feature = serde_json::from_str(
+            r#"{
+                "enabled": true,
+                "value_type": "boolean",
+                "audience_rules": [{ "name": "beta", "expression": "beta", "variant": "on" }],
+                "default_rule": { "variant": "off" }
+            }"#,
+        )
+        .expect("should deserialize");
+
+        let feature = Feature::from_config("exp", &config).expect("feature should build");
+        let actual = feature.to_config();
+        assert_eq!(
+            actual.variants,
+            BTreeMap::from([
+                ("off".to_string(), false.into()),
+                ("on".to_string(), true.into()),
+            ])
+        );
+        assert_eq!(actual.default_variant, "off");
+
+        let ctx = ContextBuilder::new()
+            .user_key("key")
+            .bool_attribute("beta", true)
+            .build()
+            .expect("context should build");
+        assert_eq!(feature.decide(&ctx).value, true.into());
+
+        let config = config::Feature {
+            default_variant: "on".to_string(),
+            ..config
+        };
+        let feature = Feature::from_config("exp", &config).expect("feature should build");
+        assert_eq!(feature.default_variant(), "on");
+
+        // the shorthand only applies to boolean features
+        let config = config::Feature {
+            value_type: ValueType::Integer,
+            ..config
+        };
+        assert!(Feature::from_config("exp", &config).is_err());
+    }
+
+    #[test]
+    fn test_feature_evaluate_salt() {
+        let new_feature = |salt: &str| {
+            FeatureBuilder::new(ValueType::Integer)
+                .name("exp")
+                .enabled(true)
+                .salt(salt)
+                .variant("a", 1.into())
+                .variant("b", 2.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 50)
+                        .variant("b", 50)
+                        .build()
+                        .expect("rule should build"),
+                )
+                .build()
+                .expect("feature should build")
+        };
+
+        let variants = |feature: &Feature| -> Vec<String> {
+            (0..100)
+                .map(|i| feature.decide(&Context::new(format!("user{}", i))).variant)
+                .collect()
+        };
+
+        let unsalted = variants(&new_feature(""));
+        assert_eq!(variants(&new_feature("")), unsalted);
+        assert_ne!(variants(&new_feature("salt")), unsalted);
+    }
+
+    #[test]
+    fn test_feature_evaluate_bucketing_key() {
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .bucketing_key("org_id")
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .default_variant("a")
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 50)
+                    .variant("b", 50)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let new_context = |user_key: &str, attributes: &str| -> Context {
+            serde_json::from_str(&format!(
+                r#"{{"user_key":"{}","attributes":{}}}"#,
+                user_key, attributes
+            ))
+            .expect("should deserialize")
+        };
+
+        // users in the same org share a hash
+        let a = feature.decide(&new_context("a", r#"{"org_id": "org"}"#));
+        let b = feature.decide(&new_context("b", r#"{"org_id": "org"}"#));
+        assert_eq!(a.hash, hash::calculate("exp", "org"));
+        assert_eq!(a, b);
+        assert!(a.warning.is_none());
+
+        // missing or non-string attributes fall back to the user key
+        for attributes in [r#"{}"#, r#"{"org_id": 1}"#] {
+            let actual = feature.decide(&new_context("a", attributes));
+            assert_eq!(actual.hash, hash::calculate("exp", "a"));
+            assert_eq!(actual.reason, Reason::Split);
+            assert!(actual.error.is_none());
+            assert!(actual.warning.is_some());
+        }
+    }
+
+    #[test]
+    fn test_feature_evaluate_anonymous() {
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .default_variant("a")
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 50)
+                    .variant("b", 50)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.hash, hash::calculate("exp", "key"));
+        assert!(!actual.anonymous);
+
+        // anonymous decisions use an ephemeral key, so repeated decisions land in different buckets
+        let buckets: HashSet<u32> = (0..10)
+            .map(|_| feature.decide(&Context::anonymous()))
+            .inspect(|d| assert!(d.anonymous))
+            .map(|d| d.bucket)
+            .collect();
+        assert!(buckets.len() > 1);
+    }
+
+    #[test]
+    fn test_feature_evaluate_off_variant() {
+        let new_feature = |enabled: bool, off_variant: Option<&str>| {
+            let mut builder = FeatureBuilder::new(ValueType::Integer)
+                .name("exp")
+                .enabled(enabled)
+                .variant("a", 1.into())
+                .variant("b", 2.into())
+                .variant("off", 0.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("b", 100)
+                        .build()
+                        .expect("rule should build"),
+                );
+            if let Some(off_variant) = off_variant {
+                builder = builder.off_variant(off_variant);
+            }
+            builder.build().expect("feature should build")
+        };
+
+        let ctx = Context::new("key");
+
+        let actual = new_feature(false, Some("off")).decide(&ctx);
+        assert_eq!(actual.reason, Reason::Disabled);
+        assert_eq!(actual.variant, "off");
+        assert_eq!(actual.value, 0.into());
+
+        let actual = new_feature(false, None).decide(&ctx);
+        assert_eq!(actual.reason, Reason::Disabled);
+        assert_eq!(actual.variant, "a");
+        assert_eq!(actual.value, 1.into());
+
+        // disabled decisions are not bucketed, so the hash is not calculated
+        for i in 0..100 {
+            let actual = new_feature(false, None).decide(&Context::new(format!("user{}", i)));
+            assert_eq!((actual.hash, actual.bucket), (0, 0));
+        }
+
+        let actual = new_feature(true, Some("off")).decide(&ctx);
+        assert_eq!(actual.reason, Reason::Static);
+        assert_eq!(actual.variant, "b");
+    }
+
+    #[test]
+    fn test_feature_evaluate_scheduled() {
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .default_variant("a")
+            .audience_rule(
+                RuleBuilder::new()
+                    .variant("b", 100)
+                    .audience("launch", "true")
+                    .starts_at("2025-01-01T00:00:00Z")
+                    .ends_at("2025-02-01T00:00:00Z")
+                    .build()
+                    .expect("rule should build"),
+            )
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let new_context = |now: &str| -> Context {
+            let mut ctx = Context::new("key");
+            ctx.attributes = Some(HashMap::from([(
+                NOW_ATTRIBUTE.to_string(),
+                Object::String(now.to_string()),
+            )]));
+            ctx
+        };
+
+        let tests = vec![
+            ("2024-12-31T00:00:00Z", "a"),
+            ("2025-01-15T00:00:00Z", "b"),
+            ("2025-02-01T00:00:00Z", "a"),
+        ];
+
+        for (now, expected) in tests {
+            let actual = feature.decide(&new_context(now));
+            assert_eq!(actual.variant, expected, "{}", now);
+        }
+
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.variant, "a");
+
+        let actual = feature.decide(&new_context("invalid"));
+        assert_eq!(actual.reason, Reason::Error);
+        assert!(actual.error.is_some());
+
+        // the current time is not parsed for features without scheduled rules
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .default_variant("a")
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("b", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let actual = feature.decide(&new_context("invalid"));
+        assert_eq!(actual.reason, Reason::Static);
+        assert_eq!(actual.variant, "b");
+        assert!(actual.error.is_none());
+    }
+
+    #[test]
+    fn test_feature_evaluate_fallback_variant() {
+        let new_feature = |fallback_variant: Option<&str>| {
+            let mut builder = FeatureBuilder::new(ValueType::Integer)
+                .name("exp")
+                .enabled(true)
+                .variant("a", 1.into())
+                .variant("b", 2.into())
+                .variant("fallback", 0.into())
+                .default_variant("a")
+                .audience_rule(
+                    RuleBuilder::new()
+                        .variant("b", 100)
+                        .audience("beta", "true.a") // runtime error
+                        .build()
+                        .expect("rule should build"),
+                )
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("b", 100)
+                        .build()
+                        .expect("rule should build"),
+                );
+            if let Some(fallback_variant) = fallback_variant {
+                builder = builder.fallback_variant(fallback_variant);
+            }
+            builder.build().expect("feature should build")
+        };
+
+        let ctx = Context::new("key");
+
+        let actual = new_feature(Some("fallback")).decide(&ctx);
+        assert_eq!(actual.reason, Reason::Error);
+        assert_eq!(actual.variant, "fallback");
+        assert_eq!(actual.value, 0.into());
+        assert!(actual.error.is_some());
+
+        let actual = new_feature(None).decide(&ctx);
+        assert_eq!(actual.reason, Reason::Error);
+        assert_eq!(actual.variant, "a");
+        assert_eq!(actual.value, 1.into());
+
+        let err = actual.error.expect("error should be set").to_string();
+        assert!(
+            err.starts_with("Targeting error: in feature 'exp' audience 'beta': [expr: true.a]: "),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_feature_evaluate_override() {
+        let new_feature = |enabled: bool| {
+            FeatureBuilder::new(ValueType::Integer)
+                .name("exp")
+                .enabled(enabled)
+                .variant("a", 1.into())
+                .variant("b", 2.into())
+                .default_variant("a")
+                .default_rule(
+                    RuleBuilder::new()
+                        .variant("a", 100)
+                        .build()
+                        .expect("rule should build"),
+                )
+                .build()
+                .expect("feature should build")
+        };
+
+        let new_context = |variant: &str| -> Context {
+            let mut ctx = Context::new("key");
+            ctx.overrides = Some(HashMap::from([("exp".to_string(), variant.to_string())]));
+            ctx
+        };
+
+        let actual = new_feature(true).decide(&new_context("b"));
+        assert_eq!(actual.reason, Reason::Override);
+        assert_eq!(actual.variant, "b");
+        assert_eq!(actual.value, 2.into());
+
+        let actual = new_feature(true).decide(&new_context("invalid"));
+        assert_eq!(actual.reason, Reason::Error);
+        assert!(actual.error.is_some());
+
+        let actual = new_feature(false).decide(&new_context("b"));
+        assert_eq!(actual.reason, Reason::Disabled);
+        assert_eq!(actual.variant, "a");
+    }
+
+    #[test]
+    fn test_feature_evaluate_fallthrough() {
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .default_variant("a")
+            .audience_rule(
+                RuleBuilder::new()
+                    .variant("b", 10)
+                    .audience("all", "true")
+                    .build()
+                    .expect("rule should build"),
+            )
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let decisions: Vec<Decision> = (0..1000)
+            .map(|i| feature.decide(&Context::new(format!("user{}", i))))
+            .collect();
+
+        for decision in decisions.iter() {
+            if decision.hash % 100 < 10 {
+                assert_eq!(decision.variant, "b");
+                assert_eq!(decision.reason, Reason::Match);
+                assert_eq!(decision.audience, Some("all".to_string()));
+            } else {
+                assert_eq!(decision.variant, "a");
+                assert_eq!(decision.reason, Reason::Static);
+                assert_eq!(decision.audience, None);
+            }
+        }
+
+        let matched = decisions.iter().filter(|d| d.variant == "b").count();
+        assert!((50..150).contains(&matched));
+    }
+
+    #[test]
+    fn test_feature_evaluate_rollout() {
+        let config: config::Feature = serde_json::from_str(
+            r#"{
+                "enabled": true,
+                "value_type": "integer",
+                "variants": { "a": 1, "b": 2 },
+                "default_variant": "a",
+                "audience_rules": [
+                    { "name": "all", "expression": "true", "variant": "b", "percentage": 20 }
+                ],
+                "default_rule": { "variant": "a" }
+            }"#,
+        )
+        .expect("should deserialize");
+
+        let feature = Feature::from_config("exp", &config).expect("feature should build");
+        assert_eq!(
+            feature.to_config().audience_rules[0].bucketing,
+            config::Bucketing::Rollout {
+                variant: "b".to_string(),
+                percentage: 20.0,
+            }
+        );
+
+        for i in 0..1000 {
+            let decision = feature.decide(&Context::new(format!("user{}", i)));
+            if decision.hash % 100 < 20 {
+                assert_eq!(decision.variant, "b");
+                assert_eq!(decision.reason, Reason::Match);
+            } else {
+                assert_eq!(decision.variant, "a");
+                assert_eq!(decision.reason, Reason::Static);
+            }
+        }
+    }
+
+    #[test]
+    fn test_feature_evaluate_dimension_rules() {
+        let config: config::Feature = serde_json::from_str(
+            r#"{
+                "enabled": true,
+                "value_type": "integer",
+                "variants": { "a": 1, "b": 2 },
+                "default_variant": "a",
+                "default_dimension": "region",
+                "dimension_rules": {
+                    "eu": { "distribution": { "a": 50, "b": 50 } },
+                    "us": { "distribution": { "a": 90, "b": 10 } }
+                },
+                "default_rule": { "variant": "a" }
+            }"#,
+        )
+        .expect("should deserialize");
+
+        let feature = Feature::from_config("exp", &config).expect("feature should build");
+        assert_eq!(feature.to_config(), config);
+
+        let count_b = |region: Option<&str>| {
+            (0..1000)
+                .map(|i| {
+                    let mut builder = ContextBuilder::new().user_key(format!("user{}", i));
+                    if let Some(region) = region {
+                        builder = builder.string_attribute("region", region);
+                    }
+
+                    let decision = feature.decide(&builder.build().expect("context should build"));
+                    assert!(decision.is_default);
+                    decision
+                })
+                .filter(|d| d.variant == "b")
+                .count()
+        };
+
+        assert!((400..600).contains(&count_b(Some("eu"))));
+        assert!((50..150).contains(&count_b(Some("us"))));
+        assert_eq!(count_b(Some("apac")), 0);
+        assert_eq!(count_b(None), 0);
+
+        // dimension rules require a dimension attribute and a fallback default rule
+        let invalid = config::Feature {
+            default_dimension: None,
+            ..config.clone()
+        };
+        assert!(Feature::from_config("exp", &invalid).is_err());
+
+        let invalid = config::Feature {
+            default_rule: None,
+            require_default: false,
+            ..config
+        };
+        assert!(Feature::from_config("exp", &invalid).is_err());
+    }
+
+    #[test]
+    fn test_feature_evaluate_require_default() {
+        let new_builder = || {
+            FeatureBuilder::new(ValueType::Integer)
+                .name("exp")
+                .enabled(true)
+                .variant("a", 1.into())
+                .variant("b", 2.into())
+                .default_variant("a")
+                .audience_rule(
+                    RuleBuilder::new()
+                        .variant("b", 100)
+                        .audience("beta", "beta")
+                        .build()
+                        .expect("rule should build"),
+                )
+        };
+
+        let result = new_builder().build();
+        assert!(matches!(result, Err(FetaError::Configuration(_))));
+
+        let feature = new_builder()
+            .require_default(false)
+            .build()
+            .expect("feature should build");
+
+        let tests = vec![(true, "b", Reason::Match), (false, "a", Reason::NoMatch)];
+        for (beta, variant, reason) in tests {
+            let ctx = ContextBuilder::new()
+                .user_key("key")
+                .bool_attribute("beta", beta)
+                .build()
+                .expect("context should build");
+
+            let decision = feature.decide(&ctx);
+            assert_eq!(decision.variant, variant);
+            assert_eq!(decision.reason, reason);
+            assert!(decision.is_success());
+            assert!(!decision.is_default);
+        }
+
+        let config = feature.to_config();
+        assert!(config.default_rule.is_none());
+        assert!(!config.require_default);
+        assert!(Feature::from_config("exp", &config).is_ok());
+    }
+
+    #[test]
+    fn test_feature_evaluate_rule_index() {
+        let new_rule = |variant: &str, audience: &str| {
+            RuleBuilder::new()
+                .variant(variant, 100)
+                .audience(audience, audience)
+                .build()
+                .expect("rule should build")
+        };
+
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .variant("c", 3.into())
+            .default_variant("a")
+            .audience_rule(new_rule("b", "beta"))
+            .audience_rule(new_rule("c", "internal"))
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let tests = vec![
+            (Some("beta"), "b", Some(0)),
+            (Some("internal"), "c", Some(1)),
+            (None, "a", Some(2)),
+        ];
+
+        for (attribute, variant, expected) in tests {
+            let mut builder = ContextBuilder::new().user_key("key");
+            if let Some(attribute) = attribute {
+                builder = builder.bool_attribute(attribute, true);
+            }
+
+            let actual = feature.decide(&builder.build().expect("context should build"));
+            assert_eq!(actual.variant, variant);
+            assert_eq!(actual.rule_index, expected, "{:?}", attribute);
+        }
+
+        let ctx = Context {
+            overrides: Some(HashMap::from([("exp".to_string(), "b".to_string())])),
+            ..Context::new("key")
+        };
+        assert_eq!(feature.decide(&ctx).rule_index, None);
+    }
+
+    #[test]
+    fn test_feature_evaluate_priority() {
+        let feature = FeatureBuilder::new(ValueType::Integer)
+            .name("exp")
+            .enabled(true)
+            .variant("a", 1.into())
+            .variant("b", 2.into())
+            .variant("c", 3.into())
+            .default_variant("a")
+            .audience_rule(
+                RuleBuilder::new()
+                    .variant("b", 100)
+                    .audience("low", "true")
+                    .priority(10)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .audience_rule(
+                RuleBuilder::new()
+                    .variant("c", 100)
+                    .audience("high", "true")
+                    .priority(1)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .audience_rule(
+                RuleBuilder::new()
+                    .variant("b", 100)
+                    .audience("tie", "true")
+                    .priority(1)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .default_rule(
+                RuleBuilder::new()
+                    .variant("a", 100)
+                    .build()
+                    .expect("rule should build"),
+            )
+            .build()
+            .expect("feature should build");
+
+        let actual = feature.decide(&Context::new("key"));
+        assert_eq!(actual.variant, "c");
+        assert_eq!(actual.audience, Some("high".to_string()));
     }
 
     #[test]
@@ -426,6 +2101,8 @@ mod tests {
                 expected: DecisionBuilder::new()
                     .value(1.into())
                     .variant("a")
+                    .is_default(true)
+                    .rule_index(2)
                     .success(Reason::Split),
             },
             TestCase {
@@ -433,6 +2110,8 @@ mod tests {
                 expected: DecisionBuilder::new()
                     .value(2.into())
                     .variant("b")
+                    .is_default(true)
+                    .rule_index(2)
                     .success(Reason::Split),
             },
             TestCase {
@@ -440,6 +2119,8 @@ mod tests {
                 expected: DecisionBuilder::new()
                     .value(3.into())
                     .variant("c")
+                    .is_default(true)
+                    .rule_index(2)
                     .success(Reason::Split),
             },
             TestCase {
@@ -449,6 +2130,7 @@ mod tests {
                     .value(4.into())
                     .variant("d")
                     .audience("beta")
+                    .rule_index(0)
                     .success(Reason::Match),
             },
             TestCase {
@@ -460,6 +2142,7 @@ mod tests {
                     .value(4.into())
                     .variant("d")
                     .audience("internal")
+                    .rule_index(1)
                     .success(Reason::MatchSplit),
             },
         ];
@@ -468,6 +2151,8 @@ mod tests {
             let actual = feature.decide(&test.context);
             let mut expected = test.expected.clone();
             expected.hash = actual.hash;
+            expected.bucket = actual.bucket;
+            expected.value_type = Some(ValueType::Integer);
             assert_eq!(actual, expected)
         }
     }
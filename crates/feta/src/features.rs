@@ -1,51 +1,542 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    io::Read,
+    sync::Arc,
+};
+
+use mexl::Environment;
 
 use crate::{
-    Feature, config,
+    Feature, FeatureBuilder, FeatureInfo, config,
     context::Context,
     decision::{Decision, DecisionBuilder},
+    diff::{self, FeaturesDiff},
     error::FetaError,
-    hash,
+    feature,
+    function::Functions,
+    hash::{self, HashAlgorithm, HashKeyFormat},
 };
 
+/// The function signature for a decision observer, which receives the feature key, context, and decision.
+type ObserverFn = dyn Fn(&str, &Context, &Decision) + Send + Sync;
+
+/// A callback that is invoked with each decision, allowing native embedders to log or track evaluations.
+#[derive(Clone)]
+struct Observer(Arc<ObserverFn>);
+
+impl fmt::Debug for Observer {
+    /// Formats the `Observer` without the callback, which cannot be formatted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+/// The source of values for `${NAME}` variables in audience expressions.
+#[derive(Debug, Clone, Default)]
+enum Vars {
+    #[default]
+    Environment,
+    Map(HashMap<String, String>),
+}
+
+impl Vars {
+    /// Looks up the value of the variable with the specified name.
+    fn lookup(&self, name: &str) -> Option<String> {
+        match self {
+            Vars::Environment => config::env_var(name),
+            Vars::Map(vars) => vars.get(name).cloned(),
+        }
+    }
+}
+
+/// The `FeaturesBuilder` struct provides a builder pattern for constructing `Features` instances with variables,
+/// host functions, and an observer, all of which are retained when the features are updated.
+#[derive(Debug, Clone, Default)]
+pub struct FeaturesBuilder {
+    vars: Vars,
+    functions: Functions,
+    observer: Option<Observer>,
+}
+
+impl FeaturesBuilder {
+    /// Creates a new `FeaturesBuilder` that reads variables from the environment and has the built-in functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the values for `${NAME}` variables in audience expressions, which are otherwise read from the environment.
+    pub fn vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = Vars::Map(vars);
+        self
+    }
+
+    /// Sets the host functions that audience expressions can call, which apply to every rule.
+    pub fn functions(mut self, functions: Functions) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// Sets a callback that is invoked with the feature key, context, and decision after each evaluation.
+    pub fn observer(
+        mut self,
+        observer: impl Fn(&str, &Context, &Decision) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+
+    /// Builds the `Features` instance from the given configuration.
+    ///
+    /// Audience expressions can reference `${NAME}` variables, which are replaced before the expressions are compiled.
+    /// Undefined variables are an error.
+    pub fn build(self, cfg: &config::Config) -> Result<Features, FetaError> {
+        cfg.check_version()?;
+        let cfg = cfg.interpolate(&|name| self.vars.lookup(name))?;
+
+        // an empty config is valid, but is often the result of loading the wrong file
+        #[cfg(feature = "tracing")]
+        if cfg.features.is_empty() {
+            tracing::warn!("config has no features");
+        }
+
+        let mut features = HashMap::with_capacity(cfg.features.len());
+
+        for (name, feature) in &cfg.features {
+            let feature =
+                FeatureBuilder::from_config_with_functions(name, feature, &self.functions)?
+                    .seed(cfg.seed)
+                    .hash_algorithm(cfg.hash_algorithm)
+                    .hash_key_format(cfg.hash_key_format)
+                    .build()?;
+
+            features.insert(name.clone(), feature);
+        }
+
+        validate_all_prerequisites(&features)?;
+        let exclusions = index_exclusion_groups(&cfg.exclusion_groups, &features)?;
+
+        Ok(Features {
+            seed: cfg.seed,
+            hash_algorithm: cfg.hash_algorithm,
+            hash_key_format: cfg.hash_key_format,
+            features,
+            exclusion_groups: cfg.exclusion_groups.clone(),
+            exclusions,
+            observer: self.observer,
+            functions: self.functions,
+            vars: self.vars,
+        })
+    }
+}
+
 /// The `Features` struct manages a collection of features.
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Features {
+    seed: u32,
+    hash_algorithm: HashAlgorithm,
+    hash_key_format: HashKeyFormat,
     features: HashMap<String, Feature>,
+    exclusion_groups: BTreeMap<String, Vec<String>>,
+    /// The exclusion group for each feature that is a member of one, keyed by feature name.
+    exclusions: HashMap<String, String>,
+    observer: Option<Observer>,
+    functions: Functions,
+    vars: Vars,
 }
 
 impl Features {
     /// Creates a `Features` instance from the given configuration.
+    ///
+    /// Audience expressions can reference `${NAME}` variables, which are replaced with environment variables
+    /// before the expressions are compiled. Undefined variables are an error.
     pub fn from_config(cfg: &config::Config) -> Result<Self, FetaError> {
-        let mut features = HashMap::with_capacity(cfg.features.len());
+        FeaturesBuilder::new().build(cfg)
+    }
+
+    /// Creates a `Features` instance from the given configuration, with host functions that audience expressions can call.
+    ///
+    /// The same functions apply to every rule, and are retained when the features are updated.
+    pub fn from_config_with_functions(
+        cfg: &config::Config,
+        functions: Functions,
+    ) -> Result<Self, FetaError> {
+        FeaturesBuilder::new().functions(functions).build(cfg)
+    }
+
+    /// Creates a `Features` instance from the given configuration, replacing `${NAME}` variables in audience
+    /// expressions with values from the specified map rather than the environment.
+    ///
+    /// The same values are used when the features are updated.
+    pub fn from_config_with_vars(
+        cfg: &config::Config,
+        vars: &HashMap<String, String>,
+    ) -> Result<Self, FetaError> {
+        FeaturesBuilder::new().vars(vars.clone()).build(cfg)
+    }
 
-        for (name, cfg) in &cfg.features {
-            features.insert(name.clone(), Feature::from_config(name, cfg)?);
+    /// Creates a `Features` instance from a JSON configuration string.
+    pub fn from_json(json: &str) -> Result<Self, FetaError> {
+        Self::from_config(&config::Config::from_json(json)?)
+    }
+
+    /// Creates a `Features` instance from a reader that produces JSON configuration.
+    pub fn from_reader(reader: impl Read) -> Result<Self, FetaError> {
+        Self::from_config(&config::Config::from_reader(reader)?)
+    }
+
+    /// Sets a callback that is invoked with the feature key, context, and decision after each evaluation.
+    ///
+    /// Prerequisite evaluations are not observed, and the observer is retained when the features are updated.
+    pub fn with_observer(
+        mut self,
+        observer: impl Fn(&str, &Context, &Decision) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+
+    /// Reconstructs the configuration for all features.
+    pub fn to_config(&self) -> config::Config {
+        config::Config {
+            version: config::SCHEMA_VERSION,
+            seed: self.seed,
+            hash_algorithm: self.hash_algorithm,
+            hash_key_format: self.hash_key_format,
+            features: self
+                .features
+                .iter()
+                .map(|(name, feature)| (name.clone(), feature.to_config()))
+                .collect(),
+            exclusion_groups: self.exclusion_groups.clone(),
         }
+    }
+
+    /// Compares the features with another set of features, reporting the changes required to match `other`.
+    ///
+    /// Features are compared by their reconstructed configuration, so rules are compared in priority order.
+    pub fn diff(&self, other: &Features) -> FeaturesDiff {
+        diff::diff(&self.to_config(), &other.to_config())
+    }
+
+    /// Replaces the features with those from the given configuration, leaving the existing features intact if it is invalid.
+    ///
+    /// The variables, functions, and observer that the features were created with are retained.
+    pub fn update_from_config(&mut self, cfg: &config::Config) -> Result<(), FetaError> {
+        *self = self.rebuild(cfg)?;
+        Ok(())
+    }
 
-        Ok(Self { features })
+    /// Builds new features from the given configuration with the same variables, functions, and observer.
+    ///
+    /// The current features are unchanged, so the new features can be built while they are in use and then swapped in.
+    pub fn rebuild(&self, cfg: &config::Config) -> Result<Self, FetaError> {
+        FeaturesBuilder {
+            vars: self.vars.clone(),
+            functions: self.functions.clone(),
+            observer: self.observer.clone(),
+        }
+        .build(cfg)
     }
 
     /// Evaluates the specified feature for the given context and returns a `Decision` with the result.
+    ///
+    /// Prerequisites are evaluated first, and the default variant is returned if any are not met.
     pub fn decide(&self, feature: &str, ctx: &Context) -> Decision {
-        match self.features.get(feature) {
-            Some(f) => f.decide(ctx),
+        self.decide_with_env(feature, ctx, &ctx.environment())
+    }
+
+    /// Evaluates the specified feature for the given context using an `Environment` created with `Context::environment`.
+    pub fn decide_with_env(&self, feature: &str, ctx: &Context, env: &Environment) -> Decision {
+        let decision = self.evaluate(feature, ctx, env);
+
+        if let Some(Observer(observer)) = &self.observer {
+            observer(feature, ctx, &decision);
+        }
+
+        decision
+    }
+
+    /// Evaluates the specified feature without notifying the observer.
+    ///
+    /// With the `tracing` feature, each evaluation is recorded in a `decide` span, which is nested for prerequisites.
+    fn evaluate(&self, feature: &str, ctx: &Context, env: &Environment) -> Decision {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "decide",
+            feature,
+            variant = tracing::field::Empty,
+            reason = tracing::field::Empty,
+            error = tracing::field::Empty,
+        )
+        .entered();
+
+        let decision = match self.features.get(feature) {
+            Some(f) => f.decide_with_prerequisites(
+                ctx,
+                env,
+                self.is_excluded(feature, ctx),
+                |feature, variant| {
+                    let decision = self.evaluate(feature, ctx, env);
+                    decision.is_success() && decision.variant == variant
+                },
+            ),
             None => DecisionBuilder::new()
-                .hash(hash::calculate(feature, &ctx.user_key))
-                .error(FetaError::Request(format!("invalid feature: {}", feature))),
+                .hash(hash::calculate_with_options(
+                    self.hash_algorithm,
+                    self.hash_key_format,
+                    feature,
+                    &ctx.user_key,
+                    self.seed,
+                ))
+                .error(FetaError::NotFound(format!("invalid feature: {}", feature))),
+        };
+
+        #[cfg(feature = "tracing")]
+        span.record("variant", decision.variant.as_str())
+            .record("reason", tracing::field::display(decision.reason))
+            .record("error", decision.is_error());
+
+        decision
+    }
+
+    /// Returns `true` if the context is assigned to a different feature in the exclusion group of the specified feature.
+    ///
+    /// The assigned feature is selected with a group-level hash of the user key, so it is consistent across evaluations.
+    /// Anonymous contexts have no stable key to coordinate the assignment, so they are excluded from every feature in a group.
+    fn is_excluded(&self, feature: &str, ctx: &Context) -> bool {
+        let Some(group) = self.exclusions.get(feature) else {
+            return false;
+        };
+
+        if ctx.anonymous {
+            return true;
         }
+
+        let members = &self.exclusion_groups[group];
+        let hash = hash::calculate_with_options(
+            self.hash_algorithm,
+            self.hash_key_format,
+            group,
+            &ctx.user_key,
+            self.seed,
+        );
+        let index = hash::to_bucket(hash) as usize * members.len() / hash::BUCKET_COUNT as usize;
+
+        members[index] != feature
     }
 
     /// Evaluates all features for the given context and returns a map of feature names to their corresponding `Decision` results.
     pub fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("decide_all", count = self.features.len()).entered();
+
         let mut results = HashMap::with_capacity(self.features.len());
+        let env = ctx.environment();
+
+        for name in self.features.keys() {
+            results.insert(name.clone(), self.decide_with_env(name, ctx, &env));
+        }
+
+        results
+    }
+
+    /// Evaluates all features for the given context and returns a map of feature names to their corresponding `Decision` results, sorted by feature name.
+    pub fn decide_all_sorted(&self, ctx: &Context) -> BTreeMap<String, Decision> {
+        let env = ctx.environment();
+
+        self.features
+            .keys()
+            .map(|name| (name.clone(), self.decide_with_env(name, ctx, &env)))
+            .collect()
+    }
+
+    /// Evaluates all features in parallel for the given context and returns a map of feature names to their corresponding `Decision` results.
+    ///
+    /// This is only faster than `decide_all` for large configurations; see `benches/decide_all.rs` to find the crossover point.
+    #[cfg(feature = "rayon")]
+    pub fn par_decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        use rayon::prelude::*;
 
-        for (name, feature) in self.features.iter() {
-            results.insert(name.clone(), feature.decide(ctx));
+        let env = ctx.environment();
+        self.features
+            .par_iter()
+            .map(|(name, _)| (name.clone(), self.decide_with_env(name, ctx, &env)))
+            .collect()
+    }
+
+    /// Evaluates the features with the specified tag for the given context and returns a map of feature names to their corresponding `Decision` results.
+    pub fn decide_tagged(&self, tag: &str, ctx: &Context) -> HashMap<String, Decision> {
+        let env = ctx.environment();
+
+        self.features
+            .iter()
+            .filter(|(_, f)| f.tags().iter().any(|t| t == tag))
+            .map(|(name, _)| (name.clone(), self.decide_with_env(name, ctx, &env)))
+            .collect()
+    }
+
+    /// Evaluates the specified features for the given context and returns a map of feature names to their corresponding `Decision` results.
+    pub fn decide_many(&self, features: &[&str], ctx: &Context) -> HashMap<String, Decision> {
+        let mut results = HashMap::with_capacity(features.len());
+        let env = ctx.environment();
+
+        for feature in features {
+            results.insert(
+                feature.to_string(),
+                self.decide_with_env(feature, ctx, &env),
+            );
         }
 
         results
     }
+
+    /// Evaluates the specified feature for each user key with no attributes and returns the number of users per variant.
+    ///
+    /// This is intended for validating rollout percentages, so simulated decisions are not passed to the observer.
+    pub fn simulate(&self, feature: &str, user_keys: &[&str]) -> HashMap<String, usize> {
+        let env = Environment::default();
+        let mut tally = HashMap::new();
+
+        for user_key in user_keys {
+            let decision = self.evaluate(feature, &Context::new(*user_key), &env);
+            *tally.entry(decision.variant).or_insert(0) += 1;
+        }
+
+        tally
+    }
+
+    /// Returns metadata for the specified feature, or `None` if it does not exist.
+    pub fn feature_info(&self, feature: &str) -> Option<FeatureInfo> {
+        self.features.get(feature).map(Feature::info)
+    }
+
+    /// Returns an iterator over the names of all features.
+    pub fn feature_names(&self) -> impl Iterator<Item = &str> {
+        self.features.keys().map(|k| k.as_str())
+    }
+
+    /// Returns the number of features.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Returns `true` if there are no features, which can be used to detect a config that was loaded in error.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+}
+
+/// Validates the given configuration, returning all errors rather than only the first.
+pub(crate) fn config_errors(cfg: &config::Config) -> Vec<FetaError> {
+    let mut errors = Vec::new();
+    let mut features = HashMap::with_capacity(cfg.features.len());
+
+    for (name, feature) in &cfg.features {
+        match feature::from_config_collecting_errors(name, feature) {
+            Ok(f) => _ = features.insert(name.clone(), f),
+            Err(e) => errors.extend(e),
+        }
+    }
+
+    // prerequisites and exclusion groups reference other features, so they can only be validated once all features are valid
+    if errors.is_empty() {
+        if let Err(e) = validate_all_prerequisites(&features) {
+            errors.push(e);
+        }
+        if let Err(e) = index_exclusion_groups(&cfg.exclusion_groups, &features) {
+            errors.push(e);
+        }
+    }
+
+    errors
+}
+
+/// Validates the exclusion groups, returning the group for each member feature keyed by feature name.
+fn index_exclusion_groups(
+    groups: &BTreeMap<String, Vec<String>>,
+    features: &HashMap<String, Feature>,
+) -> Result<HashMap<String, String>, FetaError> {
+    let mut exclusions = HashMap::new();
+    for (group, members) in groups {
+        if members.is_empty() {
+            return Err(FetaError::Configuration(format!(
+                "exclusion group has no features: {}",
+                group
+            )));
+        }
+
+        for member in members {
+            if !features.contains_key(member) {
+                return Err(FetaError::Configuration(format!(
+                    "exclusion group feature does not exist: {}",
+                    member
+                )));
+            }
+
+            if exclusions.insert(member.clone(), group.clone()).is_some() {
+                return Err(FetaError::Configuration(format!(
+                    "feature is in more than one exclusion group: {}",
+                    member
+                )));
+            }
+        }
+    }
+
+    Ok(exclusions)
+}
+
+/// Validates the prerequisites of all features.
+fn validate_all_prerequisites(features: &HashMap<String, Feature>) -> Result<(), FetaError> {
+    let mut visited = HashSet::with_capacity(features.len());
+    for name in features.keys() {
+        validate_prerequisites(name, features, &mut Vec::new(), &mut visited)?;
+    }
+
+    Ok(())
+}
+
+/// Validates that the prerequisites of the specified feature exist and do not form a cycle.
+fn validate_prerequisites<'a>(
+    name: &'a str,
+    features: &'a HashMap<String, Feature>,
+    path: &mut Vec<&'a str>,
+    visited: &mut HashSet<&'a str>,
+) -> Result<(), FetaError> {
+    if visited.contains(name) {
+        return Ok(());
+    }
+
+    if path.contains(&name) {
+        path.push(name);
+        return Err(FetaError::Configuration(format!(
+            "prerequisite cycle detected: {}",
+            path.join(" -> ")
+        )));
+    }
+
+    path.push(name);
+    for (feature, variant) in features[name].prerequisites() {
+        let prerequisite = features
+            .get(feature)
+            .ok_or(FetaError::Configuration(format!(
+                "prerequisite feature does not exist: {}",
+                feature
+            )))?;
+
+        if !prerequisite.has_variant(variant) {
+            return Err(FetaError::Configuration(format!(
+                "prerequisite variant does not exist: {}",
+                variant
+            )));
+        }
+
+        validate_prerequisites(feature, features, path, visited)?;
+    }
+    path.pop();
+
+    visited.insert(name);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -54,7 +545,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        config,
+        ContextBuilder, FeatureChange, Object, config,
         decision::Reason,
         value::{Value, ValueType},
     };
@@ -69,9 +560,13 @@ mod tests {
         let mut expected = DecisionBuilder::new()
             .variant("a")
             .value(1.into())
+            .value_type(ValueType::Integer)
+            .is_default(true)
+            .rule_index(1)
             .success(Reason::Split);
 
         expected.hash = actual.hash;
+        expected.bucket = actual.bucket;
         assert_eq!(actual, expected);
     }
 
@@ -86,7 +581,10 @@ mod tests {
         assert!(actual.hash != 0);
         assert_eq!(actual.reason, Reason::Error);
         assert_eq!(actual.value, Value::Null);
-        assert!(actual.error.is_some());
+        assert_eq!(
+            actual.error,
+            Some(FetaError::NotFound("invalid feature: invalid".to_string()))
+        );
     }
 
     #[test]
@@ -101,45 +599,991 @@ mod tests {
             DecisionBuilder::new()
                 .variant("a")
                 .value(1.into())
+                .value_type(ValueType::Integer)
+                .is_default(true)
+                .rule_index(1)
                 .success(Reason::Split),
         )]);
 
         for (key, expected) in expected.iter_mut() {
             expected.hash = actual.get(key).unwrap().hash;
+            expected.bucket = actual.get(key).unwrap().bucket;
         }
 
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_features_evaluate_all_sorted() {
+        let mut config = get_config();
+        for name in ["f3", "f0", "f2"] {
+            let feature = get_config().features.remove("f1").unwrap();
+            config.features.insert(name.to_string(), feature);
+        }
+
+        let features = Features::from_config(&config).unwrap();
+        let ctx = Context::new("g");
+
+        let actual = features.decide_all_sorted(&ctx);
+        let keys: Vec<&str> = actual.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["f0", "f1", "f2", "f3"]);
+
+        let unsorted = features.decide_all(&ctx);
+        assert!(actual.iter().all(|(k, v)| &unsorted[k] == v));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_features_evaluate_all_parallel() {
+        let config = get_config();
+        let features = Features::from_config(&config).unwrap();
+        let ctx = Context::new("g");
+
+        assert_eq!(features.par_decide_all(&ctx), features.decide_all(&ctx));
+    }
+
+    #[test]
+    fn test_features_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Features>();
+    }
+
+    #[test]
+    fn test_features_evaluate_many() {
+        let config = get_config();
+        let features = Features::from_config(&config).unwrap();
+        let ctx = Context::new("g");
+
+        let actual = features.decide_many(&["f1", "invalid"], &ctx);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual["f1"], features.decide("f1", &ctx));
+        assert_eq!(actual["invalid"].reason, Reason::Error);
+        assert!(actual["invalid"].error.is_some());
+    }
+
+    #[test]
+    fn test_features_evaluate_globals() {
+        let config = get_config();
+        let features = Features::from_config(&config).unwrap();
+
+        let mut ctx = Context::new("g");
+        ctx.globals = Some(HashMap::from([("beta".to_string(), true.into())]));
+
+        let actual = features.decide("f1", &ctx);
+        assert_eq!(actual.reason, Reason::Match);
+        assert_eq!(actual.variant, "b");
+
+        let env = ctx.environment();
+        assert_eq!(features.decide_with_env("f1", &ctx, &env), actual);
+        assert_eq!(features.decide_all(&ctx)["f1"], actual);
+
+        // attributes take precedence over globals
+        ctx.attributes = Some(HashMap::from([("beta".to_string(), false.into())]));
+        let actual = features.decide("f1", &ctx);
+        assert_eq!(actual.reason, Reason::Split);
+    }
+
+    #[test]
+    fn test_config_validate() {
+        assert!(get_config().validate().is_ok());
+
+        let mut config = get_config();
+        let mut feature = get_config().features.remove("f1").unwrap();
+        feature.default_variant = "invalid".to_string();
+        config.features.insert("f2".to_string(), feature);
+
+        let feature = config.features.get_mut("f1").unwrap();
+        feature.audience_rules[0].expression = config::Expression::Single {
+            expression: "+2".to_string(),
+        };
+        feature.audience_rules.push(config::AudienceRule {
+            name: "alpha".to_string(),
+            expression: config::Expression::Single {
+                expression: "alpha".to_string(),
+            },
+            negate: false,
+            priority: 0,
+            starts_at: Some("invalid".to_string()),
+            ends_at: None,
+            bucketing: config::Bucketing::Variant {
+                variant: "a".to_string(),
+            },
+            normalize: false,
+        });
+
+        let actual = config.validate().expect_err("config should be invalid");
+        let messages: Vec<String> = actual.iter().map(|e| e.to_string()).collect();
+
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(messages[0].starts_with("Targeting error: feature f1: rule beta: "));
+        assert_eq!(
+            messages[1],
+            "Configuration error: feature f1: rule alpha: invalid timestamp: invalid"
+        );
+        assert_eq!(
+            messages[2],
+            "Configuration error: feature f2: default variant does not exist: invalid"
+        );
+
+        // prerequisites are validated once all features are valid
+        let mut config = get_config();
+        config.features.get_mut("f1").unwrap().prerequisites = vec![config::Prerequisite {
+            feature: "invalid".to_string(),
+            variant: "a".to_string(),
+        }];
+        assert_eq!(config.validate().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn test_features_to_config() {
+        let config: config::Config = serde_json::from_str(
+            r#"{
+                "seed": 1,
+                "features": {
+                    "parent": {
+                        "enabled": true,
+                        "tags": ["checkout"],
+                        "value_type": "boolean",
+                        "variants": {"off": false, "on": true},
+                        "default_variant": "off",
+                        "default_rule": {"variant": "on"}
+                    },
+                    "child": {
+                        "enabled": false,
+                        "salt": "salt",
+                        "bucketing_key": "org_id",
+                        "value_type": "integer",
+                        "variants": {"a": 1, "b": 2, "c": 3, "off": 0},
+                        "default_variant": "a",
+                        "off_variant": "off",
+                        "fallback_variant": "c",
+                        "prerequisites": [{"feature": "parent", "variant": "on"}],
+                        "audience_rules": [
+                            {
+                                "name": "beta",
+                                "expression": "is_beta",
+                                "starts_at": "2025-01-01T00:00:00+00:00",
+                                "variant": "b"
+                            },
+                            {
+                                "name": "internal",
+                                "expression": "is_internal",
+                                "priority": 1,
+                                "distribution": {"a": 33.33, "b": 16.67}
+                            },
+                            {
+                                "name": "staff",
+                                "all": ["is_internal", "is_beta"],
+                                "priority": 2,
+                                "variant": "c"
+                            },
+                            {
+                                "name": "partner",
+                                "any": ["is_partner", "is_reseller"],
+                                "negate": true,
+                                "priority": 2,
+                                "variant": "c"
+                            }
+                        ],
+                        "default_rule": {"distribution": {"a": 33.33, "b": 33.33, "c": 33.34}}
+                    }
+                }
+            }"#,
+        )
+        .expect("should deserialize");
+
+        let features = Features::from_config(&config).unwrap();
+        let actual = features.to_config();
+        assert_eq!(actual, config);
+
+        let actual = Features::from_config(&actual).unwrap().to_config();
+        assert_eq!(actual, config);
+    }
+
+    #[test]
+    fn test_features_from_config_with_functions() {
+        let config = config::Config::from_json(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "default_variant": "off",
+                        "audience_rules": [
+                            { "name": "admins", "expression": "contains(roles, \"admin\")", "variant": "on" }
+                        ],
+                        "default_rule": { "variant": "off" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let functions = Functions::new().with_function("contains", |args| match args {
+            [Object::Array(items), value] => Ok(Object::Boolean(items.contains(value))),
+            _ => Ok(Object::Boolean(false)),
+        });
+        let mut features = Features::from_config_with_functions(&config, functions).unwrap();
+
+        let new_context = |roles: &[&str]| {
+            ContextBuilder::new()
+                .user_key("a")
+                .attribute(
+                    "roles",
+                    Object::Array(roles.iter().map(|&r| r.into()).collect()),
+                )
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(
+            features
+                .decide("f1", &new_context(&["admin", "user"]))
+                .variant,
+            "on"
+        );
+        assert_eq!(
+            features.decide("f1", &new_context(&["user"])).variant,
+            "off"
+        );
+
+        // functions are retained when the features are updated
+        features.update_from_config(&config).unwrap();
+        assert_eq!(
+            features.decide("f1", &new_context(&["admin"])).variant,
+            "on"
+        );
+    }
+
+    #[test]
+    fn test_features_update_from_config() {
+        let mut features = Features::from_config(&get_config()).unwrap();
+        let ctx = Context::new("g");
+        let expected = features.decide("f1", &ctx);
+
+        let mut config = get_config();
+        config.features.get_mut("f1").unwrap().default_variant = "invalid".to_string();
+        assert!(features.update_from_config(&config).is_err());
+        assert_eq!(features.decide("f1", &ctx), expected);
+
+        let mut config = get_config();
+        let feature = config.features.remove("f1").unwrap();
+        config.features.insert("f2".to_string(), feature);
+        config.seed = 1;
+        features.update_from_config(&config).unwrap();
+
+        let actual: Vec<&str> = features.feature_names().collect();
+        assert_eq!(actual, vec!["f2"]);
+        assert_eq!(
+            features.decide("f2", &ctx).hash,
+            hash::calculate_with_seed("f2", "g", 1)
+        );
+    }
+
+    #[test]
+    fn test_features_feature_info() {
+        let features = Features::from_config(&get_config()).unwrap();
+
+        let actual = features.feature_info("f1").expect("feature should exist");
+        let expected = FeatureInfo {
+            name: "f1".to_string(),
+            enabled: true,
+            value_type: ValueType::Integer,
+            variants: vec!["a".to_string(), "b".to_string()],
+            default_variant: "a".to_string(),
+        };
+        assert_eq!(actual, expected);
+
+        assert!(features.feature_info("invalid").is_none());
+    }
+
+    #[test]
+    fn test_features_from_json() {
+        let json = r#"{
+            "features": {
+                "f1": {
+                    "enabled": true,
+                    "value_type": "boolean",
+                    "variants": { "off": false, "on": true },
+                    "default_variant": "off",
+                    "default_rule": { "variant": "on" }
+                }
+            }
+        }"#;
+
+        type FromFn = fn(&str) -> Result<Features, FetaError>;
+        let tests: Vec<(&str, FromFn)> = vec![
+            ("from_json", Features::from_json),
+            ("from_reader", |s| Features::from_reader(s.as_bytes())),
+        ];
+
+        for (name, from) in tests {
+            let features = from(json).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            let actual = features.decide("f1", &Context::new("a"));
+            assert_eq!(actual.variant, "on", "{}", name);
+
+            let err = from(r#"{"features": {"#).expect_err(name);
+            assert!(matches!(err, FetaError::Configuration(_)), "{}", name);
+
+            // parse succeeds but the feature is invalid, which is reported by the build stage
+            let invalid = json.replace(r#""default_variant": "off""#, r#""default_variant": "x""#);
+            let err = from(&invalid).expect_err(name);
+            assert!(matches!(err, FetaError::Configuration(_)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_features_from_config_with_vars() {
+        let config: config::Config = serde_json::from_str(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "variants": { "off": false, "on": true },
+                        "default_variant": "off",
+                        "audience_rules": [
+                            { "name": "local", "all": ["region eq \"${REGION}\"", "tier eq \"${TIER}\""], "variant": "on" }
+                        ],
+                        "default_rule": { "variant": "off" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let vars = HashMap::from([
+            ("REGION".to_string(), "eu".to_string()),
+            ("TIER".to_string(), "gold".to_string()),
+        ]);
+        let mut features = Features::from_config_with_vars(&config, &vars).unwrap();
+
+        let new_context = |region: &str| {
+            ContextBuilder::new()
+                .user_key("a")
+                .string_attribute("region", region)
+                .string_attribute("tier", "gold")
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(features.decide("f1", &new_context("eu")).variant, "on");
+        assert_eq!(features.decide("f1", &new_context("us")).variant, "off");
+
+        // the same values are used when the features are updated
+        features.update_from_config(&config).unwrap();
+        assert_eq!(features.decide("f1", &new_context("eu")).variant, "on");
+
+        let vars = HashMap::from([("REGION".to_string(), "eu".to_string())]);
+        let err = Features::from_config_with_vars(&config, &vars).unwrap_err();
+        assert_eq!(
+            err,
+            FetaError::Configuration(
+                "in feature 'f1' audience 'local': undefined variable in expression: TIER"
+                    .to_string()
+            )
+        );
+
+        // the process environment is used by default
+        let mut config = config;
+        config.features.get_mut("f1").unwrap().audience_rules[0].expression =
+            config::Expression::Single {
+                expression: "region eq \"${FETA_TEST_UNDEFINED}\"".to_string(),
+            };
+        assert!(Features::from_config(&config).is_err());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_features_builder() {
+        let config = config::Config::from_json(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "default_variant": "off",
+                        "audience_rules": [
+                            { "name": "local", "expression": "eq_fold(region, \"${REGION}\")", "variant": "on" }
+                        ],
+                        "default_rule": { "variant": "off" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let functions = Functions::new().with_function("eq_fold", |args| match args {
+            [Object::String(a), Object::String(b)] => {
+                Ok(Object::Boolean(a.eq_ignore_ascii_case(b)))
+            }
+            _ => Ok(Object::Boolean(false)),
+        });
+
+        let observed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = observed.clone();
+        let mut features = FeaturesBuilder::new()
+            .vars(HashMap::from([("REGION".to_string(), "EU".to_string())]))
+            .functions(functions)
+            .observer(move |_, _, _| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            })
+            .build(&config)
+            .unwrap();
+
+        let ctx = ContextBuilder::new()
+            .user_key("a")
+            .string_attribute("region", "eu")
+            .build()
+            .unwrap();
+
+        assert_eq!(features.decide("f1", &ctx).variant, "on");
+
+        // variables, functions, and the observer are retained when the features are updated
+        features.update_from_config(&config).unwrap();
+        assert_eq!(features.decide("f1", &ctx).variant, "on");
+        assert_eq!(observed.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_features_config_version() {
+        let mut config: config::Config =
+            serde_json::from_str(r#"{"features": {}}"#).expect("should deserialize");
+        assert_eq!(config.version, 1);
+        assert!(Features::from_config(&config).is_ok());
+
+        config.version = 2;
+        let err = Features::from_config(&config).unwrap_err();
+        assert_eq!(
+            err,
+            FetaError::Configuration("unsupported config version: 2 (supported: 1)".to_string())
+        );
+        assert_eq!(config.validate(), Err(vec![err]));
+    }
+
+    #[test]
+    fn test_features_diff() {
+        let current = get_config();
+        let features = Features::from_config(&current).unwrap();
+        assert!(features.diff(&features.clone()).is_empty());
+
+        let mut config = get_config();
+        let f1 = config.features.get_mut("f1").unwrap();
+        f1.default_rule = Some(config::DefaultRule {
+            bucketing: config::Bucketing::Distribution {
+                distribution: BTreeMap::from([("a".to_string(), 90.0), ("b".to_string(), 10.0)]),
+            },
+            normalize: false,
+        });
+        f1.enabled = false;
+
+        let mut f2 = get_config().features.remove("f1").unwrap();
+        f2.tags = vec!["new".to_string()];
+        config.features.insert("f2".to_string(), f2);
+        config.seed = 1;
+
+        let other = Features::from_config(&config).unwrap();
+        let actual = features.diff(&other);
+
+        let expected = FeaturesDiff {
+            added: vec!["f2".to_string()],
+            removed: vec![],
+            modified: BTreeMap::from([(
+                "f1".to_string(),
+                vec![FeatureChange::Enabled, FeatureChange::DefaultRule],
+            )]),
+            hashing_changed: true,
+            exclusion_groups_changed: false,
+        };
+        assert_eq!(actual, expected);
+
+        let reverse = other.diff(&features);
+        assert_eq!(reverse.removed, vec!["f2".to_string()]);
+        assert!(reverse.added.is_empty());
+    }
+
+    #[test]
+    fn test_features_observer() {
+        let observed: Arc<std::sync::Mutex<Vec<(String, String)>>> = Arc::default();
+
+        let mut config = get_config();
+        let mut feature = get_config().features.remove("f1").unwrap();
+        feature.prerequisites = vec![config::Prerequisite {
+            feature: "f1".to_string(),
+            variant: "a".to_string(),
+        }];
+        config.features.insert("f2".to_string(), feature);
+
+        let captured = observed.clone();
+        let features =
+            Features::from_config(&config)
+                .unwrap()
+                .with_observer(move |feature, ctx, decision| {
+                    let mut captured = captured.lock().unwrap();
+                    captured.push((feature.to_string(), ctx.user_key.clone()));
+                    assert!(!decision.is_error());
+                });
+
+        let ctx = Context::new("g");
+        features.decide("f2", &ctx);
+        features.clone().decide_all_sorted(&ctx);
+
+        // the f1 prerequisite evaluation is not observed, and batch evaluation order is unspecified
+        let mut actual = observed.lock().unwrap().clone();
+        actual[1..].sort();
+        let expected: Vec<(String, String)> = ["f2", "f1", "f2"]
+            .into_iter()
+            .map(|f| (f.to_string(), "g".to_string()))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_features_clone() {
+        let mut features = Features::from_config(&get_config()).unwrap();
+        let snapshot = features.clone();
+        let ctx = Context::new("g");
+
+        let mut config = get_config();
+        config.features.get_mut("f1").unwrap().enabled = false;
+        features.update_from_config(&config).unwrap();
+
+        assert_eq!(features.decide("f1", &ctx).reason, Reason::Disabled);
+        assert_ne!(snapshot.decide("f1", &ctx).reason, Reason::Disabled);
+        assert!(format!("{:?}", snapshot).contains(r#"name: "f1""#));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_features_evaluate_tracing() {
+        use std::sync::{Arc, Mutex};
+
+        use tracing::{
+            Subscriber,
+            field::{Field, Visit},
+            span::{Attributes, Id, Record},
+        };
+        use tracing_subscriber::{Layer, layer::Context as LayerContext, prelude::*};
+
+        type Fields = Arc<Mutex<Vec<(String, String)>>>;
+
+        struct Capture(Fields);
+
+        impl Visit for Capture {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let mut fields = self.0.lock().unwrap();
+                fields.push((field.name().to_string(), format!("{:?}", value)));
+            }
+        }
+
+        struct CaptureLayer(Fields);
+
+        impl<S: Subscriber> Layer<S> for CaptureLayer {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: LayerContext<'_, S>) {
+                attrs.record(&mut Capture(self.0.clone()));
+            }
+
+            fn on_record(&self, _: &Id, values: &Record<'_>, _: LayerContext<'_, S>) {
+                values.record(&mut Capture(self.0.clone()));
+            }
+        }
+
+        let fields: Fields = Arc::default();
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer(fields.clone()));
+
+        let features = Features::from_config(&get_config()).unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            features.decide("f1", &Context::new("g"));
+        });
+
+        let actual = fields.lock().unwrap().clone();
+        let expected: Vec<(String, String)> = [
+            ("feature", r#""f1""#),
+            ("variant", r#""a""#),
+            ("reason", "split"),
+            ("error", "false"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_features_evaluate_tagged() {
+        let mut config = get_config();
+        for (name, tags) in [("f2", vec!["checkout"]), ("f3", vec!["search", "checkout"])] {
+            let mut feature = get_config().features.remove("f1").unwrap();
+            feature.tags = tags.into_iter().map(String::from).collect();
+            config.features.insert(name.to_string(), feature);
+        }
+
+        let features = Features::from_config(&config).unwrap();
+        let ctx = Context::new("g");
+
+        let actual = features.decide_tagged("checkout", &ctx);
+        let mut keys: Vec<&str> = actual.keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["f2", "f3"]);
+        assert_eq!(actual["f2"], features.decide("f2", &ctx));
+
+        assert_eq!(features.decide_tagged("search", &ctx).len(), 1);
+        assert!(features.decide_tagged("invalid", &ctx).is_empty());
+    }
+
+    #[test]
+    fn test_features_evaluate_seed() {
+        let mut config = get_config();
+        let features = Features::from_config(&config).unwrap();
+
+        config.seed = 1;
+        let seeded = Features::from_config(&config).unwrap();
+
+        let ctx = Context::new("g");
+        assert_eq!(features.decide("f1", &ctx).hash, hash::calculate("f1", "g"));
+        assert_eq!(
+            seeded.decide("f1", &ctx).hash,
+            hash::calculate_with_seed("f1", "g", 1)
+        );
+        assert_ne!(
+            features.decide("f1", &ctx).hash,
+            seeded.decide("f1", &ctx).hash
+        );
+    }
+
+    #[test]
+    fn test_features_evaluate_hash_algorithm() {
+        let mut config = get_config();
+        let murmur3 = Features::from_config(&config).unwrap();
+
+        config.hash_algorithm = HashAlgorithm::XxHash32;
+        let xxhash32 = Features::from_config(&config).unwrap();
+
+        let ctx = Context::new("g");
+        let expected = hash::calculate_with_algorithm(HashAlgorithm::XxHash32, "f1", "g", 0);
+        assert_eq!(xxhash32.decide("f1", &ctx).hash, expected);
+        assert_eq!(
+            xxhash32.clone().decide("f1", &ctx),
+            xxhash32.decide("f1", &ctx)
+        );
+
+        assert_eq!(murmur3.decide("f1", &ctx).hash, hash::calculate("f1", "g"));
+        assert_ne!(
+            murmur3.decide("f1", &ctx).bucket,
+            xxhash32.decide("f1", &ctx).bucket
+        );
+
+        assert_eq!(xxhash32.to_config().hash_algorithm, HashAlgorithm::XxHash32);
+    }
+
+    #[test]
+    fn test_features_evaluate_hash_key_format() {
+        let mut config = get_config();
+        config.hash_key_format = HashKeyFormat::Separated;
+        let separated = Features::from_config(&config).unwrap();
+
+        let ctx = Context::new("g");
+        let expected = hash::calculate_with_options(
+            HashAlgorithm::Murmur3,
+            HashKeyFormat::Separated,
+            "f1",
+            "g",
+            0,
+        );
+        assert_eq!(separated.decide("f1", &ctx).hash, expected);
+        assert_ne!(expected, hash::calculate("f1", "g"));
+        assert_eq!(
+            separated.to_config().hash_key_format,
+            HashKeyFormat::Separated
+        );
+        assert!(
+            separated
+                .diff(&Features::from_config(&get_config()).unwrap())
+                .hashing_changed
+        );
+    }
+
+    #[test]
+    fn test_features_feature_names() {
+        let config = get_config();
+        let features = Features::from_config(&config).unwrap();
+
+        let actual: Vec<&str> = features.feature_names().collect();
+
+        assert_eq!(actual, vec!["f1"]);
+        assert_eq!(features.len(), 1);
+        assert!(!features.is_empty());
+        assert!(Features::default().is_empty());
+    }
+
+    #[test]
+    fn test_features_empty_config() {
+        let features = Features::from_json(r#"{"features": {}}"#).unwrap();
+        let ctx = Context::new("user_key");
+
+        assert!(features.is_empty());
+        assert!(features.decide_all(&ctx).is_empty());
+        assert!(matches!(
+            features.decide("f1", &ctx).error,
+            Some(FetaError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_features_evaluate_prerequisites() {
+        let new_config = |parent_enabled: bool| -> config::Config {
+            serde_json::from_str(&format!(
+                r#"{{
+                    "features": {{
+                        "parent": {{
+                            "enabled": {},
+                            "value_type": "boolean",
+                            "variants": {{"off": false, "on": true}},
+                            "default_variant": "off",
+                            "default_rule": {{"variant": "on"}}
+                        }},
+                        "child": {{
+                            "enabled": true,
+                            "value_type": "boolean",
+                            "variants": {{"off": false, "on": true}},
+                            "default_variant": "off",
+                            "prerequisites": [{{"feature": "parent", "variant": "on"}}],
+                            "default_rule": {{"variant": "on"}}
+                        }}
+                    }}
+                }}"#,
+                parent_enabled
+            ))
+            .expect("should deserialize")
+        };
+        let ctx = Context::new("g");
+
+        let features = Features::from_config(&new_config(true)).unwrap();
+        let actual = features.decide("child", &ctx);
+        assert_eq!(actual.reason, Reason::Static);
+        assert_eq!(actual.variant, "on");
+
+        let features = Features::from_config(&new_config(false)).unwrap();
+        let actual = features.decide("child", &ctx);
+        assert_eq!(actual.reason, Reason::Prerequisite);
+        assert_eq!(actual.variant, "off");
+        assert_eq!(actual.value, false.into());
+        assert_eq!(features.decide_all(&ctx)["child"], actual);
+
+        // a prerequisite decision with a warning still satisfies the prerequisite
+        let mut config = new_config(true);
+        config.features.get_mut("parent").unwrap().bucketing_key = Some("account_id".to_string());
+        let features = Features::from_config(&config).unwrap();
+        assert!(features.decide("parent", &ctx).warning.is_some());
+        let actual = features.decide("child", &ctx);
+        assert_eq!(actual.reason, Reason::Static);
+        assert_eq!(actual.variant, "on");
+
+        // prerequisites are not evaluated for standalone features
+        let feature = Feature::from_config("child", &new_config(false).features["child"]).unwrap();
+        assert_eq!(feature.decide(&ctx).reason, Reason::Static);
+    }
+
+    #[test]
+    fn test_features_simulate() {
+        let features = Features::from_config(&get_config())
+            .unwrap()
+            .with_observer(|_, _, _| panic!("simulated decisions should not be observed"));
+
+        let keys: Vec<String> = (0..10_000).map(|i| format!("user{}", i)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        let actual = features.simulate("f1", &keys);
+        assert_eq!(actual.values().sum::<usize>(), keys.len());
+        for variant in ["a", "b"] {
+            assert!((4_800..5_200).contains(&actual[variant]), "{:?}", actual);
+        }
+
+        assert_eq!(actual, features.simulate("f1", &keys));
+        assert_eq!(
+            features.simulate("invalid", &keys[..10]),
+            HashMap::from([(String::new(), 10)])
+        );
+    }
+
+    #[test]
+    fn test_features_exclusion_groups() {
+        let mut config = get_config();
+        for name in ["f2", "f3"] {
+            let feature = get_config().features.remove("f1").unwrap();
+            config.features.insert(name.to_string(), feature);
+        }
+        config.exclusion_groups = BTreeMap::from([(
+            "checkout".to_string(),
+            vec!["f1".to_string(), "f2".to_string()],
+        )]);
+
+        let features = Features::from_config(&config).unwrap();
+        let mut enrolled = HashMap::new();
+
+        for i in 0..1000 {
+            let ctx = Context::new(format!("user{}", i));
+            let decisions = features.decide_all(&ctx);
+
+            let members: Vec<&str> = ["f1", "f2"]
+                .into_iter()
+                .filter(|f| decisions[*f].reason != Reason::Excluded)
+                .collect();
+            assert_eq!(members.len(), 1, "user{}", i);
+            *enrolled.entry(members[0]).or_insert(0) += 1;
+
+            // the excluded feature returns the default variant, and features outside the group are unaffected
+            let excluded = if members[0] == "f1" { "f2" } else { "f1" };
+            assert_eq!(decisions[excluded].variant, "a");
+            assert_eq!(decisions[excluded].value, 1.into());
+            assert_ne!(decisions["f3"].reason, Reason::Excluded);
+
+            assert_eq!(features.decide(excluded, &ctx), decisions[excluded]);
+        }
+
+        assert!((400..600).contains(&enrolled["f1"]), "{:?}", enrolled);
+
+        let ctx = Context::anonymous();
+        assert_eq!(features.decide("f1", &ctx).reason, Reason::Excluded);
+        assert_eq!(features.decide("f2", &ctx).reason, Reason::Excluded);
+        assert_eq!(
+            features.to_config().exclusion_groups,
+            config.exclusion_groups
+        );
+    }
+
+    #[test]
+    fn test_features_exclusion_group_errors() {
+        let tests = vec![
+            (
+                vec!["f1", "invalid"],
+                "exclusion group feature does not exist: invalid",
+            ),
+            (
+                vec!["f1", "f1"],
+                "feature is in more than one exclusion group: f1",
+            ),
+            (vec![], "exclusion group has no features: g"),
+        ];
+
+        for (members, expected) in tests {
+            let mut config = get_config();
+            config.exclusion_groups = BTreeMap::from([(
+                "g".to_string(),
+                members.iter().map(|m| m.to_string()).collect(),
+            )]);
+
+            let err = Features::from_config(&config).expect_err("config should be invalid");
+            assert_eq!(err, FetaError::Configuration(expected.to_string()));
+            assert_eq!(
+                config
+                    .validate()
+                    .expect_err("config should be invalid")
+                    .len(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_features_prerequisite_errors() {
+        let new_config = |prerequisites: &[(&str, &str)]| -> config::Config {
+            let mut config = get_config();
+            let mut feature = get_config().features.remove("f1").unwrap();
+            feature.prerequisites = vec![config::Prerequisite {
+                feature: "f1".to_string(),
+                variant: "a".to_string(),
+            }];
+            config.features.insert("f2".to_string(), feature);
+
+            config.features.get_mut("f1").unwrap().prerequisites = prerequisites
+                .iter()
+                .map(|(f, v)| config::Prerequisite {
+                    feature: f.to_string(),
+                    variant: v.to_string(),
+                })
+                .collect();
+            config
+        };
+
+        assert!(Features::from_config(&new_config(&[])).is_ok());
+
+        let tests = vec![
+            (
+                vec![("invalid", "a")],
+                "prerequisite feature does not exist: invalid",
+            ),
+            (
+                vec![("f2", "invalid")],
+                "prerequisite variant does not exist: invalid",
+            ),
+            (vec![("f1", "a")], "prerequisite cycle detected"),
+            (vec![("f2", "a")], "prerequisite cycle detected"),
+        ];
+
+        for (prerequisites, expected) in tests {
+            match Features::from_config(&new_config(&prerequisites)) {
+                Err(FetaError::Configuration(msg)) => assert!(msg.starts_with(expected), "{}", msg),
+                _ => panic!("expected configuration error for {:?}", prerequisites),
+            }
+        }
+    }
+
     fn get_config() -> config::Config {
         config::Config {
+            version: 1,
+            seed: 0,
+            hash_algorithm: HashAlgorithm::Murmur3,
+            hash_key_format: HashKeyFormat::Concatenated,
             features: BTreeMap::from([(
                 "f1".to_string(),
                 config::Feature {
                     enabled: true,
+                    tags: vec![],
                     value_type: ValueType::Integer,
+                    element_type: None,
+                    salt: String::new(),
+                    bucketing_key: None,
                     variants: BTreeMap::from([
                         ("a".to_string(), 1.into()),
                         ("b".to_string(), 2.into()),
                     ]),
+                    variant_metadata: BTreeMap::new(),
                     default_variant: "a".to_string(),
-                    default_rule: config::DefaultRule {
+                    off_variant: None,
+                    fallback_variant: None,
+                    prerequisites: vec![],
+                    default_rule: Some(config::DefaultRule {
                         bucketing: config::Bucketing::Distribution {
                             distribution: BTreeMap::from([
-                                ("a".to_string(), 50),
-                                ("b".to_string(), 50),
+                                ("a".to_string(), 50.0),
+                                ("b".to_string(), 50.0),
                             ]),
                         },
-                    },
+                        normalize: false,
+                    }),
+                    default_dimension: None,
+                    dimension_rules: BTreeMap::new(),
+                    require_default: true,
                     audience_rules: vec![config::AudienceRule {
                         name: "beta".to_string(),
-                        expression: "beta".to_string(),
+                        expression: config::Expression::Single {
+                            expression: "beta".to_string(),
+                        },
+                        negate: false,
+                        priority: 0,
+                        starts_at: None,
+                        ends_at: None,
                         bucketing: config::Bucketing::Variant {
                             variant: "b".to_string(),
                         },
+                        normalize: false,
                     }],
                 },
             )]),
+            exclusion_groups: BTreeMap::new(),
         }
     }
 }
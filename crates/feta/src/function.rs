@@ -0,0 +1,391 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use mexl::Object;
+
+use crate::{context::Context, error::FetaError};
+
+/// The function signature for a host function, which receives the evaluated arguments and returns the result.
+type FunctionFn = dyn Fn(&[Object]) -> Result<Object, FetaError> + Send + Sync;
+
+/// A host function that can be called from audience expressions.
+#[derive(Clone)]
+struct Function(Arc<FunctionFn>);
+
+impl fmt::Debug for Function {
+    /// Formats the `Function` without the callback, which cannot be formatted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Function")
+    }
+}
+
+/// A table of named host functions that can be called from audience expressions, such as `semver_gte(app_version, "2.1.0")`.
+///
+/// The built-in `contains(list, value)` function is always available unless it is replaced.
+/// The same functions apply to every rule. Calls are evaluated against the context before the expression is run, so arguments
+/// must be attribute names or string, number, boolean or null literals, and calls cannot be nested.
+#[derive(Debug, Clone)]
+pub struct Functions(HashMap<String, Function>);
+
+impl Default for Functions {
+    /// Creates a new `Functions` table with the built-in functions.
+    fn default() -> Self {
+        Self(HashMap::new()).with_function("contains", contains)
+    }
+}
+
+impl Functions {
+    /// Creates a new `Functions` table with the built-in functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a function with the specified name, replacing any existing function with the same name.
+    ///
+    /// Missing attribute arguments are passed as `Object::Null`, and errors are reported as decision errors.
+    pub fn with_function(
+        mut self,
+        name: impl Into<String>,
+        function: impl Fn(&[Object]) -> Result<Object, FetaError> + Send + Sync + 'static,
+    ) -> Self {
+        self.0.insert(name.into(), Function(Arc::new(function)));
+        self
+    }
+}
+
+/// Returns true if the list contains the value or the string contains the substring, where a missing list contains nothing.
+fn contains(args: &[Object]) -> Result<Object, FetaError> {
+    match args {
+        [Object::Array(items), value] => Ok(Object::Boolean(items.contains(value))),
+        [Object::String(s), Object::String(sub)] => Ok(Object::Boolean(s.contains(sub.as_str()))),
+        [Object::Null, _] => Ok(Object::Boolean(false)),
+        [_, _] => Err(FetaError::Targeting(
+            "contains requires a list or string".to_string(),
+        )),
+        _ => Err(FetaError::Targeting(
+            "contains requires 2 arguments".to_string(),
+        )),
+    }
+}
+
+/// An argument to a host function call, which is either a literal or an attribute resolved from the context.
+#[derive(Debug, Clone, PartialEq)]
+enum Argument {
+    Literal(Object),
+    Attribute(String),
+}
+
+/// A host function call in an audience expression, which is replaced by a variable that is bound to the call result.
+#[derive(Debug, Clone)]
+pub(crate) struct Call {
+    pub(crate) variable: String,
+    name: String,
+    function: Function,
+    args: Vec<Argument>,
+}
+
+impl Call {
+    /// Calls the function with the arguments resolved from the context.
+    pub(crate) fn evaluate(&self, ctx: &Context) -> Result<Object, FetaError> {
+        let args: Vec<Object> = self
+            .args
+            .iter()
+            .map(|arg| match arg {
+                Argument::Literal(value) => value.clone(),
+                Argument::Attribute(key) => ctx.attribute(key).cloned().unwrap_or(Object::Null),
+            })
+            .collect();
+
+        (self.function.0)(&args).map_err(|e| e.with_context(&format!("[call: {}]", self.name)))
+    }
+}
+
+/// Replaces calls to the specified functions in the expression with variables, returning the rewritten expression and the calls.
+///
+/// Variables are numbered from `first`, so that calls in different expressions of the same rule do not collide.
+/// They start with a letter so that they are valid identifiers without relying on underscore prefixes.
+pub(crate) fn extract_calls(
+    expr: &str,
+    functions: &Functions,
+    first: usize,
+) -> Result<(String, Vec<Call>), FetaError> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut output = String::with_capacity(expr.len());
+    let mut calls = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '"' || c == '\'' {
+            let end = string_end(&chars, i).unwrap_or(chars.len());
+            output.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        if !is_identifier_start(c) {
+            output.push(c);
+            i += 1;
+            continue;
+        }
+
+        // identifiers are consumed whole, and members of other values are never calls
+        let start = i;
+        while i < chars.len() && is_identifier_char(chars[i]) {
+            i += 1;
+        }
+        let name: String = chars[start..i].iter().collect();
+        let is_member = start > 0 && chars[start - 1] == '.';
+
+        let open = (i..chars.len())
+            .find(|&j| !chars[j].is_whitespace())
+            .filter(|&j| chars[j] == '(');
+
+        match (functions.0.get(&name), open) {
+            (Some(function), Some(open)) if !is_member => {
+                let close = call_end(&chars, open, &name)?;
+                let args: String = chars[open + 1..close].iter().collect();
+                let call = Call {
+                    variable: format!("feta_call_{}", first + calls.len()),
+                    args: parse_arguments(&args, &name)?,
+                    function: function.clone(),
+                    name,
+                };
+
+                output.push_str(&call.variable);
+                calls.push(call);
+                i = close + 1;
+            }
+            _ => output.push_str(&name),
+        }
+    }
+
+    Ok((output, calls))
+}
+
+/// Returns true if the character can start an identifier.
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+/// Returns true if the character can appear in an identifier.
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Returns the index after the closing quote of the string literal that starts at `start`, or `None` if it is not closed.
+fn string_end(chars: &[char], start: usize) -> Option<usize> {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// Returns the index of the closing parenthesis of the call that opens at `open`.
+fn call_end(chars: &[char], open: usize, name: &str) -> Result<usize, FetaError> {
+    let mut i = open + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '"' | '\'' => i = string_end(chars, i).unwrap_or(chars.len()),
+            ')' => return Ok(i),
+            '(' => {
+                return Err(FetaError::Targeting(format!(
+                    "nested calls are not supported in call to {}",
+                    name
+                )));
+            }
+            _ => i += 1,
+        }
+    }
+
+    Err(FetaError::Targeting(format!("unclosed call to {}", name)))
+}
+
+/// Parses the comma separated arguments of a call.
+fn parse_arguments(args: &str, name: &str) -> Result<Vec<Argument>, FetaError> {
+    let chars: Vec<char> = args.chars().collect();
+    if chars.iter().all(|c| c.is_whitespace()) {
+        return Ok(Vec::new());
+    }
+
+    let mut parsed = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i <= chars.len() {
+        match chars.get(i) {
+            Some('"' | '\'') => i = string_end(&chars, i).unwrap_or(chars.len()),
+            Some(',') | None => {
+                let arg: String = chars[start..i].iter().collect();
+                parsed.push(parse_argument(arg.trim(), name)?);
+                start = i + 1;
+                i += 1;
+            }
+            Some(_) => i += 1,
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Parses a single call argument, which must be a literal or an attribute name.
+fn parse_argument(arg: &str, name: &str) -> Result<Argument, FetaError> {
+    let unsupported =
+        || FetaError::Targeting(format!("unsupported argument in call to {}: {}", name, arg));
+
+    let mut chars = arg.chars();
+    let literal = match chars.next() {
+        Some(quote @ ('"' | '\'')) => {
+            let body: Vec<char> = chars.collect();
+            match body.split_last() {
+                Some((&last, body)) if last == quote => Object::String(unescape(body)),
+                _ => return Err(unsupported()),
+            }
+        }
+        Some(c) if c.is_ascii_digit() || c == '-' => match arg.parse::<i64>() {
+            Ok(i) => Object::Integer(i),
+            Err(_) => arg
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(Object::Float)
+                .ok_or_else(unsupported)?,
+        },
+        _ => match arg {
+            "true" => Object::Boolean(true),
+            "false" => Object::Boolean(false),
+            "null" => Object::Null,
+            _ if arg.starts_with(is_identifier_start) && arg.chars().all(is_identifier_char) => {
+                return Ok(Argument::Attribute(arg.to_string()));
+            }
+            _ => return Err(unsupported()),
+        },
+    };
+
+    Ok(Argument::Literal(literal))
+}
+
+/// Replaces escape sequences in the body of a string literal.
+fn unescape(body: &[char]) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut chars = body.iter();
+    while let Some(&c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => output.push('\n'),
+            Some('t') => output.push('\t'),
+            Some(&c) => output.push(c),
+            None => output.push('\\'),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_calls() {
+        let functions = Functions::new()
+            .with_function("f", |_| Ok(Object::Boolean(true)))
+            .with_function("g", |_| Ok(Object::Boolean(true)));
+
+        let tests = vec![
+            ("is_beta", "is_beta", vec![]),
+            ("f()", "feta_call_1", vec![vec![]]),
+            (
+                r#"f(roles, "a\"b", 1, 2.5, -3, true, null) and g (x)"#,
+                "feta_call_1 and feta_call_2",
+                vec![
+                    vec![
+                        Argument::Attribute("roles".to_string()),
+                        Argument::Literal(Object::String("a\"b".to_string())),
+                        Argument::Literal(Object::Integer(1)),
+                        Argument::Literal(Object::Float(2.5)),
+                        Argument::Literal(Object::Integer(-3)),
+                        Argument::Literal(Object::Boolean(true)),
+                        Argument::Literal(Object::Null),
+                    ],
+                    vec![Argument::Attribute("x".to_string())],
+                ],
+            ),
+            // strings, members and unregistered functions are not calls
+            (
+                r#"name eq "f(x)" or user.f(x) or lower(f2) eq 'g(y)'"#,
+                r#"name eq "f(x)" or user.f(x) or lower(f2) eq 'g(y)'"#,
+                vec![],
+            ),
+        ];
+
+        for (input, expected, args) in tests {
+            let (actual, calls) = extract_calls(input, &functions, 1).expect("should extract");
+            assert_eq!(actual, expected, "{}", input);
+            assert_eq!(
+                calls.into_iter().map(|c| c.args).collect::<Vec<_>>(),
+                args,
+                "{}",
+                input
+            );
+        }
+
+        for input in ["f(g(x))", "f(x", "f(a.b)", "f(x y)", r#"f("a)"#] {
+            let result = extract_calls(input, &functions, 0);
+            assert!(matches!(result, Err(FetaError::Targeting(_))), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let roles = Object::Array(vec!["admin".into(), "user".into()]);
+        let tests = vec![
+            (vec![roles.clone(), "admin".into()], Ok(true)),
+            (vec![roles, "guest".into()], Ok(false)),
+            (
+                vec!["admin@company.com".into(), "@company".into()],
+                Ok(true),
+            ),
+            (vec![Object::Null, "admin".into()], Ok(false)),
+            (vec![Object::Integer(1), "admin".into()], Err(())),
+            (vec![Object::Null], Err(())),
+        ];
+
+        for (input, expected) in tests {
+            let actual = contains(&input).map_err(|_| ());
+            assert_eq!(actual, expected.map(Object::Boolean), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_call_evaluate() {
+        let functions = Functions::new().with_function("count", |args| match args {
+            [Object::Array(items)] => Ok(Object::Integer(items.len() as i64)),
+            _ => Err(FetaError::Targeting("count requires a list".to_string())),
+        });
+
+        let (_, calls) = extract_calls("count(roles) gt 1", &functions, 0).unwrap();
+        let ctx = crate::ContextBuilder::new()
+            .user_key("a")
+            .attribute("roles", Object::Array(vec!["a".into(), "b".into()]))
+            .build()
+            .unwrap();
+        assert_eq!(calls[0].evaluate(&ctx).unwrap(), Object::Integer(2));
+
+        let err = calls[0].evaluate(&Context::new("a")).unwrap_err();
+        assert_eq!(
+            err,
+            FetaError::Targeting("[call: count]: count requires a list".to_string())
+        );
+    }
+}
@@ -1,11 +1,201 @@
-use std::io::Cursor;
+use std::{
+    hash::{BuildHasher, RandomState},
+    io::Cursor,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of buckets that users are hashed into, giving a resolution of 0.01 percent.
+pub(crate) const BUCKET_COUNT: u32 = 10_000;
+
+/// The algorithm used to hash user keys into buckets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    /// The 32-bit Murmur3 algorithm, which is used by default.
+    #[default]
+    Murmur3,
+    /// The 32-bit xxHash algorithm, for parity with systems that bucket using xxHash.
+    #[serde(rename = "xxhash32")]
+    XxHash32,
+}
+
+/// How the feature and user key are combined into the key that is hashed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashKeyFormat {
+    /// The feature and user key are concatenated, which is used by default to preserve existing bucket assignments.
+    ///
+    /// Distinct pairs can produce the same key, so `("ab", "c")` and `("a", "bc")` are always in the same bucket.
+    #[default]
+    Concatenated,
+    /// The feature and user key are separated by a null byte, so that distinct pairs produce distinct keys.
+    Separated,
+}
 
 /// Calculates a hash value for the given feature and user key using the Murmur3 algorithm.
 pub fn calculate(feature: &str, user_key: &str) -> u32 {
-    let mut key = String::new();
+    calculate_with_seed(feature, user_key, 0)
+}
+
+/// Calculates a hash value for the given feature and user key using the Murmur3 algorithm with the specified seed.
+pub fn calculate_with_seed(feature: &str, user_key: &str, seed: u32) -> u32 {
+    calculate_with_algorithm(HashAlgorithm::Murmur3, feature, user_key, seed)
+}
+
+/// Calculates a hash value for the given feature and user key using the specified algorithm and seed.
+pub fn calculate_with_algorithm(
+    algorithm: HashAlgorithm,
+    feature: &str,
+    user_key: &str,
+    seed: u32,
+) -> u32 {
+    calculate_with_options(
+        algorithm,
+        HashKeyFormat::Concatenated,
+        feature,
+        user_key,
+        seed,
+    )
+}
+
+/// Calculates a hash value for the given feature and user key using the specified algorithm, key format and seed.
+pub fn calculate_with_options(
+    algorithm: HashAlgorithm,
+    key_format: HashKeyFormat,
+    feature: &str,
+    user_key: &str,
+    seed: u32,
+) -> u32 {
+    let mut key = String::with_capacity(feature.len() + user_key.len() + 1);
     key.push_str(feature);
+    if key_format == HashKeyFormat::Separated {
+        key.push('\0');
+    }
     key.push_str(user_key);
 
-    // there are no error paths for Cursor::read, so we can assume this will succeed
-    murmur3::murmur3_32(&mut Cursor::new(&key), 0).unwrap()
+    match algorithm {
+        // there are no error paths for Cursor::read, so we can assume this will succeed
+        HashAlgorithm::Murmur3 => murmur3::murmur3_32(&mut Cursor::new(&key), seed).unwrap(),
+        HashAlgorithm::XxHash32 => xxhash_rust::xxh32::xxh32(key.as_bytes(), seed),
+    }
+}
+
+/// Calculates the canonical bucket for the given feature and user key, between 0 and 10,000.
+///
+/// This is the bucket that rules compare against variant ranges, where each bucket represents 0.01 percent.
+/// Features with a salt, seed or alternative hash algorithm must hash with those values and use the raw hash.
+pub fn bucket(feature: &str, user_key: &str) -> u32 {
+    to_bucket(calculate(feature, user_key))
+}
+
+/// Maps a hash value to a bucket between 0 and `BUCKET_COUNT`.
+///
+/// The whole percent is taken from the low digits of the hash so that whole percentage rollouts
+/// assign users to the same variants as the original 100 bucket implementation.
+pub(crate) fn to_bucket(hash: u32) -> u32 {
+    (hash % 100) * 100 + (hash / 100) % 100
+}
+
+/// Generates a random ephemeral key for hashing contexts that do not have a stable user key.
+pub(crate) fn ephemeral_key() -> String {
+    // each RandomState is seeded with distinct random keys, so the hash of a constant is random
+    format!("{:016x}", RandomState::new().hash_one(0u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_with_algorithm() {
+        let tests = [
+            (HashAlgorithm::Murmur3, "feature", "user", 0),
+            (HashAlgorithm::XxHash32, "feature", "user", 0),
+            (HashAlgorithm::XxHash32, "feature", "user", 1),
+        ];
+
+        let actual: Vec<u32> = tests
+            .iter()
+            .map(|(a, f, k, s)| calculate_with_algorithm(*a, f, k, *s))
+            .collect();
+
+        assert_eq!(actual[0], calculate("feature", "user"));
+        assert_eq!(
+            actual[1],
+            xxhash_rust::xxh32::xxh32("featureuser".as_bytes(), 0)
+        );
+        assert_ne!(actual[0], actual[1]);
+        assert_ne!(actual[1], actual[2]);
+
+        for ((a, f, k, s), expected) in tests.iter().zip(actual) {
+            assert_eq!(calculate_with_algorithm(*a, f, k, *s), expected);
+        }
+    }
+
+    #[test]
+    fn test_calculate_with_options() {
+        for algorithm in [HashAlgorithm::Murmur3, HashAlgorithm::XxHash32] {
+            let hash = |key_format, feature, user_key| {
+                calculate_with_options(algorithm, key_format, feature, user_key, 0)
+            };
+
+            assert_eq!(
+                hash(HashKeyFormat::Concatenated, "ab", "c"),
+                calculate_with_algorithm(algorithm, "ab", "c", 0)
+            );
+            assert_eq!(
+                hash(HashKeyFormat::Concatenated, "ab", "c"),
+                hash(HashKeyFormat::Concatenated, "a", "bc")
+            );
+            assert_ne!(
+                hash(HashKeyFormat::Separated, "ab", "c"),
+                hash(HashKeyFormat::Separated, "a", "bc")
+            );
+        }
+
+        let actual: HashKeyFormat =
+            serde_json::from_str(r#""separated""#).expect("should deserialize");
+        assert_eq!(actual, HashKeyFormat::Separated);
+    }
+
+    #[test]
+    fn test_hash_algorithm_serde() {
+        let actual: Vec<HashAlgorithm> =
+            serde_json::from_str(r#"["murmur3", "xxhash32"]"#).expect("should deserialize");
+        assert_eq!(actual, [HashAlgorithm::Murmur3, HashAlgorithm::XxHash32]);
+    }
+
+    #[test]
+    fn test_bucket() {
+        let rule = crate::RuleBuilder::new()
+            .variant("a", 25)
+            .variant("b", 75)
+            .build()
+            .expect("rule should build");
+
+        for i in 0..1_000 {
+            let user_key = format!("user{}", i);
+            let actual = bucket("feature", &user_key);
+            assert!(actual < BUCKET_COUNT);
+
+            let expected = if actual < 2_500 { "a" } else { "b" };
+            let variant = rule.get_variant(calculate("feature", &user_key));
+            assert_eq!(variant, Some(expected), "{}", user_key);
+        }
+    }
+
+    #[test]
+    fn test_to_bucket() {
+        for hash in [0, 1, 99, 100, 12_345, 99_999, u32::MAX] {
+            let actual = to_bucket(hash);
+            assert!(actual < BUCKET_COUNT);
+            assert_eq!(actual / 100, hash % 100);
+        }
+    }
+
+    #[test]
+    fn test_ephemeral_key() {
+        assert_ne!(ephemeral_key(), ephemeral_key());
+    }
 }
@@ -1,20 +1,40 @@
 mod context;
 mod decision;
+mod diff;
 mod error;
+mod evaluator;
 mod feature;
 mod features;
+mod function;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "test-util")]
+mod mock;
 mod rule;
+#[cfg(feature = "reload")]
+mod source;
 mod value;
 
 pub mod config;
 pub mod hash;
 
-pub use crate::context::Context;
+pub use crate::context::{Context, ContextBuilder, NOW_ATTRIBUTE};
 pub use crate::decision::{Decision, DecisionBuilder, Reason};
+pub use crate::diff::{FeatureChange, FeaturesDiff};
 pub use crate::error::FetaError;
-pub use crate::feature::{Feature, FeatureBuilder};
-pub use crate::features::Features;
+pub use crate::evaluator::Evaluator;
+pub use crate::feature::{Feature, FeatureBuilder, FeatureInfo};
+pub use crate::features::{Features, FeaturesBuilder};
+pub use crate::function::Functions;
+#[cfg(feature = "metrics")]
+pub use crate::metrics::MeteredFeatures;
+#[cfg(feature = "test-util")]
+pub use crate::mock::MockEvaluator;
 pub use crate::rule::{Rule, RuleBuilder};
+#[cfg(feature = "redis")]
+pub use crate::source::RedisConfigSource;
+#[cfg(feature = "reload")]
+pub use crate::source::{ConfigSource, FeaturesHandle, FileConfigSource, Watcher};
 pub use crate::value::{Value, ValueType};
 
 pub use mexl::Object;
@@ -0,0 +1,213 @@
+use std::{collections::HashMap, time::Instant};
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+
+use crate::{Context, Decision, Evaluator, Features, error::FetaError};
+
+/// A wrapper around `Features` that records Prometheus metrics for each decision.
+///
+/// The counters are `feta_decisions_total`, labelled by feature, variant and reason, and `feta_errors_total`, labelled by feature.
+/// Evaluation latency is recorded in the `feta_decision_duration_seconds` histogram, labelled by feature.
+///
+/// Decisions for features that do not exist are recorded with the feature label `unknown`, so that callers cannot create unbounded series.
+pub struct MeteredFeatures {
+    features: Features,
+    decisions: IntCounterVec,
+    errors: IntCounterVec,
+    durations: HistogramVec,
+}
+
+impl MeteredFeatures {
+    /// Creates a new `MeteredFeatures`, registering the metrics with the specified registry.
+    pub fn new(features: Features, registry: &Registry) -> Result<Self, FetaError> {
+        let decisions = IntCounterVec::new(
+            Opts::new("feta_decisions_total", "The number of feature decisions."),
+            &["feature", "variant", "reason"],
+        )
+        .map_err(metrics_error)?;
+
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "feta_errors_total",
+                "The number of failed feature decisions.",
+            ),
+            &["feature"],
+        )
+        .map_err(metrics_error)?;
+
+        // evaluations typically take microseconds, so the buckets range from 1us to around 250ms
+        let buckets = prometheus::exponential_buckets(1e-6, 4.0, 10).map_err(metrics_error)?;
+        let durations = HistogramVec::new(
+            HistogramOpts::new(
+                "feta_decision_duration_seconds",
+                "The duration of feature evaluations.",
+            )
+            .buckets(buckets),
+            &["feature"],
+        )
+        .map_err(metrics_error)?;
+
+        registry
+            .register(Box::new(decisions.clone()))
+            .map_err(metrics_error)?;
+        registry
+            .register(Box::new(errors.clone()))
+            .map_err(metrics_error)?;
+        registry
+            .register(Box::new(durations.clone()))
+            .map_err(metrics_error)?;
+
+        Ok(Self {
+            features,
+            decisions,
+            errors,
+            durations,
+        })
+    }
+
+    /// Returns the wrapped features.
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// Evaluates the specified feature for the given context, recording the decision.
+    pub fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        let start = Instant::now();
+        let decision = self.features.decide(feature, ctx);
+        self.record(feature, &decision, start);
+        decision
+    }
+
+    /// Evaluates all features for the given context, recording each decision.
+    ///
+    /// Features are evaluated individually with a shared environment, so the duration of each is recorded separately.
+    pub fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        let env = ctx.environment();
+
+        self.features
+            .feature_names()
+            .map(|feature| {
+                let start = Instant::now();
+                let decision = self.features.decide_with_env(feature, ctx, &env);
+                self.record(feature, &decision, start);
+                (feature.to_string(), decision)
+            })
+            .collect()
+    }
+
+    /// Records the metrics for the specified decision, with the duration measured from `start`.
+    fn record(&self, feature: &str, decision: &Decision, start: Instant) {
+        let feature = match decision.error {
+            Some(FetaError::NotFound(_)) => UNKNOWN_FEATURE,
+            _ => feature,
+        };
+
+        self.durations
+            .with_label_values(&[feature])
+            .observe(start.elapsed().as_secs_f64());
+
+        let reason = decision.reason.to_string();
+        self.decisions
+            .with_label_values(&[feature, decision.variant.as_str(), reason.as_str()])
+            .inc();
+
+        if decision.is_error() {
+            self.errors.with_label_values(&[feature]).inc();
+        }
+    }
+}
+
+impl Evaluator for MeteredFeatures {
+    fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        MeteredFeatures::decide(self, feature, ctx)
+    }
+
+    fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        MeteredFeatures::decide_all(self, ctx)
+    }
+}
+
+/// The feature label recorded for features that do not exist.
+const UNKNOWN_FEATURE: &str = "unknown";
+
+/// Converts a Prometheus error into a `FetaError`.
+fn metrics_error(err: prometheus::Error) -> FetaError {
+    FetaError::Configuration(format!("failed to register metrics: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    #[test]
+    fn test_metered_features() {
+        let config: config::Config = serde_json::from_str(
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "integer",
+                        "variants": {"a": 1, "b": 2},
+                        "default_variant": "a",
+                        "default_rule": {"variant": "b"}
+                    },
+                    "f2": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "variants": {"off": false, "on": true},
+                        "default_variant": "off",
+                        "default_rule": {"variant": "on"}
+                    }
+                }
+            }"#,
+        )
+        .expect("should deserialize");
+
+        let registry = Registry::new();
+        let features = Features::from_config(&config).unwrap();
+        let metered = MeteredFeatures::new(features, &registry).expect("should register");
+        let ctx = Context::new("g");
+
+        metered.decide("f1", &ctx);
+        metered.decide_all(&ctx);
+        metered.decide("invalid", &ctx);
+        metered.decide("other", &ctx);
+
+        // unknown features share a label, so no series are created for the requested names
+        let labels: Vec<String> = registry
+            .gather()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .flat_map(|metric| metric.get_label())
+            .filter(|label| label.name() == "feature")
+            .map(|label| label.value().to_string())
+            .collect();
+        assert!(
+            labels
+                .iter()
+                .all(|label| label != "invalid" && label != "other")
+        );
+
+        let decisions = |labels: &[&str]| metered.decisions.with_label_values(labels).get();
+        assert_eq!(decisions(&["f1", "b", "static"]), 2);
+
+        let durations = |feature: &str| {
+            let histogram = metered.durations.with_label_values(&[feature]);
+            (histogram.get_sample_count(), histogram.get_sample_sum())
+        };
+        let (count, sum) = durations("f1");
+        assert_eq!(count, 2);
+        assert!(sum > 0.0);
+        assert_eq!(durations("f2").0, 1);
+        assert_eq!(durations("unknown").0, 2);
+        assert_eq!(decisions(&["unknown", "", "error"]), 2);
+        assert_eq!(metered.errors.with_label_values(&["f1"]).get(), 0);
+        assert_eq!(metered.errors.with_label_values(&["unknown"]).get(), 2);
+
+        // registering the same metrics twice fails
+        let features = Features::from_config(&config).unwrap();
+        assert!(MeteredFeatures::new(features, &registry).is_err());
+        assert_eq!(registry.gather().len(), 3);
+    }
+}
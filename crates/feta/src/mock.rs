@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::{
+    Evaluator,
+    context::Context,
+    decision::{Decision, DecisionBuilder, Reason},
+    error::FetaError,
+    value::Value,
+};
+
+/// An `Evaluator` that returns registered decisions regardless of context, for testing code that consumes features.
+///
+/// Unregistered features return the same not found error decision as `Features`.
+#[derive(Debug, Clone, Default)]
+pub struct MockEvaluator {
+    decisions: HashMap<String, Decision>,
+}
+
+impl MockEvaluator {
+    /// Creates a new `MockEvaluator` with no registered decisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the decision to return for the specified feature.
+    pub fn with_decision(mut self, feature: impl Into<String>, decision: Decision) -> Self {
+        self.decisions.insert(feature.into(), decision);
+        self
+    }
+
+    /// Registers a static decision with the specified variant and value for the feature.
+    pub fn with_variant(
+        self,
+        feature: impl Into<String>,
+        variant: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Self {
+        let decision = DecisionBuilder::new()
+            .variant(variant)
+            .value(value.into())
+            .success(Reason::Static);
+
+        self.with_decision(feature, decision)
+    }
+}
+
+impl Evaluator for MockEvaluator {
+    fn decide(&self, feature: &str, _ctx: &Context) -> Decision {
+        match self.decisions.get(feature) {
+            Some(d) => d.clone(),
+            None => DecisionBuilder::new()
+                .error(FetaError::NotFound(format!("invalid feature: {}", feature))),
+        }
+    }
+
+    fn decide_all(&self, _ctx: &Context) -> HashMap<String, Decision> {
+        self.decisions.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the checkout flow for the context, as code under test that consumes features would.
+    fn checkout_flow(evaluator: &impl Evaluator, ctx: &Context) -> String {
+        evaluator.decide("checkout", ctx).string_or("classic")
+    }
+
+    #[test]
+    fn test_mock_evaluator() {
+        let ctx = Context::new("a");
+        let evaluator = MockEvaluator::new().with_variant("checkout", "b", "express");
+
+        assert_eq!(checkout_flow(&evaluator, &ctx), "express");
+        assert_eq!(checkout_flow(&MockEvaluator::new(), &ctx), "classic");
+
+        let actual = evaluator.decide("invalid", &ctx);
+        assert!(matches!(actual.error, Some(FetaError::NotFound(_))));
+
+        let evaluator = evaluator.with_decision("disabled", DecisionBuilder::new().disabled());
+        let actual = evaluator.decide_all(&ctx);
+        assert_eq!(actual.len(), 2);
+        assert!(actual["disabled"].is_disabled());
+    }
+}
@@ -1,12 +1,81 @@
-use mexl::{Environment, Program};
+use std::{collections::HashSet, fmt};
 
-use crate::{decision::Reason, error::FetaError};
+use chrono::{DateTime, FixedOffset};
+use mexl::{Environment, Object, Program};
+
+use crate::{
+    config,
+    context::Context,
+    decision::Reason,
+    error::FetaError,
+    function::{self, Call, Functions},
+    hash::{self, BUCKET_COUNT},
+};
+
+/// Converts a percentage to a number of buckets, returning an error if it cannot be represented.
+fn percentage_buckets(percentage: f64) -> Result<u32, FetaError> {
+    let buckets = percentage * (BUCKET_COUNT / 100) as f64;
+    if !buckets.is_finite() || buckets < 0.0 || (buckets - buckets.round()).abs() > 1e-6 {
+        return Err(FetaError::Configuration(format!(
+            "invalid variant percentage: {}",
+            percentage
+        )));
+    }
+
+    Ok(buckets.round() as u32)
+}
+
+/// Converts relative weights to a number of buckets for each weight, scaled so that all buckets are allocated.
+///
+/// Cumulative bounds are rounded rather than individual sizes, so rounding errors never accumulate.
+fn normalized_buckets(percentages: &[(String, f64)]) -> Result<Vec<u32>, FetaError> {
+    if let Some((_, w)) = percentages.iter().find(|(_, w)| !w.is_finite() || *w < 0.0) {
+        return Err(FetaError::Configuration(format!(
+            "invalid variant weight: {}",
+            w
+        )));
+    }
+
+    let total: f64 = percentages.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        return Err(FetaError::Configuration(
+            "variant weights must total more than zero".to_string(),
+        ));
+    }
+
+    let mut cumulative = 0.0;
+    let mut bound: u32 = 0;
+    Ok(percentages
+        .iter()
+        .map(|(_, w)| {
+            cumulative += w;
+            let upper = ((cumulative / total) * BUCKET_COUNT as f64).round() as u32;
+            let size = upper.min(BUCKET_COUNT) - bound;
+            bound += size;
+            size
+        })
+        .collect())
+}
+
+/// How multiple audience expressions are combined to determine whether a rule applies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Combinator {
+    All,
+    Any,
+}
 
 /// The `RuleBuilder` struct provides a builder pattern for constructing `Rule` instances.
 #[derive(Debug, Clone)]
 pub struct RuleBuilder {
-    percentages: Vec<(String, u8)>,
-    audience: Option<(String, String)>,
+    percentages: Vec<(String, f64)>,
+    audience: Option<(String, Combinator, Vec<String>)>,
+    negate: bool,
+    is_default: bool,
+    priority: i32,
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    normalize: bool,
+    functions: Option<Functions>,
 }
 
 impl Default for RuleBuilder {
@@ -22,32 +91,147 @@ impl RuleBuilder {
         Self {
             percentages: Vec::new(),
             audience: None,
+            negate: false,
+            is_default: false,
+            priority: 0,
+            starts_at: None,
+            ends_at: None,
+            normalize: false,
+            functions: None,
         }
     }
 
-    /// Adds a variant with the specified percentage to the rule.
-    pub fn variant(mut self, variant: impl Into<String>, percentage: u8) -> Self {
-        self.percentages.push((variant.into(), percentage));
+    /// Adds a variant with the specified percentage to the rule, which can be specified to two decimal places.
+    pub fn variant(mut self, variant: impl Into<String>, percentage: impl Into<f64>) -> Self {
+        self.percentages.push((variant.into(), percentage.into()));
+        self
+    }
+
+    /// Adds all of the specified variants and percentages to the rule.
+    ///
+    /// Buckets are allocated in iteration order, so use an ordered collection to keep assignments stable between builds.
+    pub fn distribution(
+        self,
+        distribution: impl IntoIterator<Item = (impl Into<String>, impl Into<f64>)>,
+    ) -> Self {
+        distribution
+            .into_iter()
+            .fold(self, |builder, (variant, percentage)| {
+                builder.variant(variant, percentage)
+            })
+    }
+
+    /// Sets whether the variant percentages are treated as relative weights and scaled to cover all buckets.
+    ///
+    /// This tolerates distributions that do not total exactly 100, such as `33, 33, 33`.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Sets the audience for the rule, which applies when the expression evaluates to true.
+    pub fn audience(self, audience: impl Into<String>, expression: impl Into<String>) -> Self {
+        self.audience_all(audience, [expression])
+    }
+
+    /// Sets the audience for the rule, which applies when all of the expressions evaluate to true.
+    pub fn audience_all(
+        mut self,
+        audience: impl Into<String>,
+        expressions: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let expressions = expressions.into_iter().map(Into::into).collect();
+        self.audience = Some((audience.into(), Combinator::All, expressions));
+        self
+    }
+
+    /// Sets the audience for the rule, which applies when any of the expressions evaluate to true.
+    pub fn audience_any(
+        mut self,
+        audience: impl Into<String>,
+        expressions: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let expressions = expressions.into_iter().map(Into::into).collect();
+        self.audience = Some((audience.into(), Combinator::Any, expressions));
+        self
+    }
+
+    /// Sets whether the audience result is inverted, so that the rule applies when the expressions do not match.
+    pub fn negate(mut self, negate: bool) -> Self {
+        self.negate = negate;
+        self
+    }
+
+    /// Marks the rule as a default rule, which must not have an audience or schedule and must cover 100 percent of buckets.
+    pub fn as_default(mut self) -> Self {
+        self.is_default = true;
         self
     }
 
-    /// Marks the rule as the default rule, which applies when no audience rules match.
-    pub fn audience(mut self, audience: impl Into<String>, expression: impl Into<String>) -> Self {
-        self.audience = Some((audience.into(), expression.into()));
+    /// Sets the priority of the rule, where rules with lower numbers are evaluated first.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets the RFC3339 timestamp from which the rule is applicable.
+    pub fn starts_at(mut self, starts_at: impl Into<String>) -> Self {
+        self.starts_at = Some(starts_at.into());
+        self
+    }
+
+    /// Sets the RFC3339 timestamp from which the rule is no longer applicable.
+    pub fn ends_at(mut self, ends_at: impl Into<String>) -> Self {
+        self.ends_at = Some(ends_at.into());
+        self
+    }
+
+    /// Sets the host functions that can be called from the audience expressions.
+    pub fn functions(mut self, functions: Functions) -> Self {
+        self.functions = Some(functions);
         self
     }
 
     /// Builds the `Rule` instance from the provided configuration.
+    ///
+    /// Audience expressions are evaluated against an empty environment, which rejects constant non-boolean expressions such as `1 + 2`.
+    /// Expressions over attributes, such as `orders + 1`, cannot be checked without attribute values, so they build successfully
+    /// and are not applicable when evaluated.
     pub fn build(self) -> Result<Rule, FetaError> {
+        let mut variants = HashSet::with_capacity(self.percentages.len());
+        for (variant, _) in &self.percentages {
+            if variant.is_empty() {
+                return Err(FetaError::Configuration(
+                    "variant name must not be empty".to_string(),
+                ));
+            }
+            if !variants.insert(variant) {
+                return Err(FetaError::Configuration(format!(
+                    "duplicate variant in rule: {}",
+                    variant
+                )));
+            }
+        }
+
+        let sizes = if self.normalize {
+            normalized_buckets(&self.percentages)?
+        } else {
+            self.percentages
+                .iter()
+                .map(|(_, p)| percentage_buckets(*p))
+                .collect::<Result<Vec<u32>, FetaError>>()?
+        };
+
         let mut bound: u32 = 0;
         let buckets: Vec<Bucket> = self
             .percentages
             .into_iter()
-            .map(|(k, p)| {
+            .zip(sizes)
+            .map(|((k, _), size)| {
                 let b = Bucket {
-                    variant: k.clone(),
+                    variant: k,
                     lower_bound: bound,
-                    upper_bound: bound + p as u32,
+                    upper_bound: bound + size,
                 };
 
                 bound = b.upper_bound;
@@ -55,23 +239,73 @@ impl RuleBuilder {
             })
             .collect();
 
-        if buckets.is_empty() || bound != 100 {
+        if buckets.is_empty() || bound > BUCKET_COUNT {
             return Err(FetaError::Configuration(
                 "invalid variant configuration".to_string(),
             ));
         }
 
+        // this is unreachable while buckets are allocated sequentially, but guards against overlaps
+        if buckets
+            .windows(2)
+            .any(|w| w[0].upper_bound != w[1].lower_bound)
+        {
+            return Err(FetaError::Configuration(
+                "variant buckets must be contiguous".to_string(),
+            ));
+        }
+
+        if self.is_default {
+            if self.audience.is_some() {
+                return Err(FetaError::Configuration(
+                    "audience not permitted for default rule".to_string(),
+                ));
+            }
+            if self.starts_at.is_some() || self.ends_at.is_some() {
+                return Err(FetaError::Configuration(
+                    "default rule must not be scheduled".to_string(),
+                ));
+            }
+            if bound < BUCKET_COUNT {
+                return Err(FetaError::Configuration(
+                    "default rule percentages must total 100".to_string(),
+                ));
+            }
+        }
+
         let mut reason = match buckets.len() {
             0 => unreachable!(),
             1 => Reason::Static,
             _ => Reason::Split,
         };
 
-        let mut program = None;
+        let mut expressions = Vec::new();
+        let mut combinator = Combinator::All;
         let mut audience = None;
-        if let Some((aud, expr)) = self.audience {
+        if let Some((aud, comb, sources)) = self.audience {
+            if sources.is_empty() {
+                return Err(FetaError::Configuration(
+                    "audience requires at least one expression".to_string(),
+                ));
+            }
+
+            // calls are numbered across all expressions, as they are bound in the same environment
+            let functions = self.functions.unwrap_or_default();
+            let mut call_count = 0;
+            expressions = sources
+                .into_iter()
+                .map(|source| {
+                    let (expr, calls) = function::extract_calls(&source, &functions, call_count)?;
+                    call_count += calls.len();
+                    Ok(Expression {
+                        program: compile(&expr)?,
+                        source,
+                        calls,
+                    })
+                })
+                .collect::<Result<Vec<Expression>, FetaError>>()?;
+            combinator = comb;
             audience = Some(aud);
-            program = Some(mexl::compile(&expr).map_err(|e| FetaError::Targeting(e.to_string()))?);
 
             reason = match reason {
                 Reason::Static => Reason::Match,
@@ -80,22 +314,88 @@ impl RuleBuilder {
             };
         };
 
+        if self.negate && audience.is_none() {
+            return Err(FetaError::Configuration(
+                "negated rule requires an audience".to_string(),
+            ));
+        }
+
+        let starts_at = self.starts_at.as_deref().map(parse_timestamp).transpose()?;
+        let ends_at = self.ends_at.as_deref().map(parse_timestamp).transpose()?;
+        if let (Some(s), Some(e)) = (starts_at, ends_at)
+            && s >= e
+        {
+            return Err(FetaError::Configuration(
+                "rule must start before it ends".to_string(),
+            ));
+        }
+
         Ok(Rule {
             buckets,
-            program,
+            expressions,
+            combinator,
+            negate: self.negate,
+            is_default: self.is_default,
             reason,
             audience,
+            priority: self.priority,
+            starts_at,
+            ends_at,
         })
     }
 }
 
+/// Parses an RFC3339 timestamp, returning an error if it is invalid.
+pub(crate) fn parse_timestamp(timestamp: &str) -> Result<DateTime<FixedOffset>, FetaError> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|_| FetaError::Configuration(format!("invalid timestamp: {}", timestamp)))
+}
+
+/// Compiles the audience expression, returning an error if it returns a constant non-boolean result.
+fn compile(expr: &str) -> Result<Program, FetaError> {
+    let program = mexl::compile(expr).map_err(|e| FetaError::Targeting(e.to_string()))?;
+
+    // the result type is only known at runtime, so evaluate against an empty environment and
+    // reject constant results; null and errors are allowed as they depend on missing attributes
+    match mexl::run(&program, &Environment::default()) {
+        Ok(Object::Boolean(_)) | Ok(Object::Null) | Err(_) => Ok(program),
+        Ok(_) => Err(FetaError::Targeting(format!(
+            "audience expression must return a boolean: {}",
+            expr
+        ))),
+    }
+}
+
 /// The `Rule` struct represents a targeting rule that determines how users are bucketed into variants based on their attributes and a hash value.
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Rule {
     buckets: Vec<Bucket>,
-    pub(crate) program: Option<Program>,
+    expressions: Vec<Expression>,
+    combinator: Combinator,
+    negate: bool,
+    pub(crate) is_default: bool,
     pub(crate) audience: Option<String>,
     pub(crate) reason: Reason,
+    pub(crate) priority: i32,
+    starts_at: Option<DateTime<FixedOffset>>,
+    ends_at: Option<DateTime<FixedOffset>>,
+}
+
+/// A compiled audience expression, along with the source it was compiled from and any host function calls.
+#[derive(Clone)]
+struct Expression {
+    source: String,
+    program: Program,
+    calls: Vec<Call>,
+}
+
+impl fmt::Debug for Expression {
+    /// Formats the `Expression` using its source, as the compiled program is an implementation detail.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Expression")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Bucket configuration for a rule, defining the variant and the hash range that maps to that variant.
@@ -107,26 +407,162 @@ pub struct Bucket {
 }
 
 impl Rule {
-    /// Evaluates whether the rule is applicable to the given environment by evaluating the audience expression if one exists or returning true if not.
-    pub fn is_applicable(&self, env: &Environment) -> Result<bool, FetaError> {
-        match &self.program {
-            Some(p) => {
-                let result = mexl::run(p, env).map_err(|e| FetaError::Targeting(e.to_string()))?;
-                Ok(result == true.into())
+    /// Evaluates whether the rule is applicable to the given context by evaluating the audience expressions if any exist or returning true if not.
+    ///
+    /// Expressions are evaluated in order and short-circuit once the result is known, so later expressions may not be evaluated.
+    /// The combined result is inverted for negated rules, but the schedule is not.
+    ///
+    /// Scheduled rules are only applicable if `now` falls within the schedule, and are never applicable if `now` is not known.
+    pub fn is_applicable(
+        &self,
+        ctx: &Context,
+        now: Option<DateTime<FixedOffset>>,
+    ) -> Result<bool, FetaError> {
+        self.is_applicable_in(ctx, &ctx.environment(), now)
+    }
+
+    /// Evaluates whether the rule is applicable to the given context, reusing `env` for expressions without host function calls.
+    ///
+    /// `env` must be created with `Context::environment`, so that it can be shared between rules.
+    pub(crate) fn is_applicable_in(
+        &self,
+        ctx: &Context,
+        env: &Environment,
+        now: Option<DateTime<FixedOffset>>,
+    ) -> Result<bool, FetaError> {
+        if self.is_scheduled() {
+            let Some(now) = now else {
+                return Ok(false);
+            };
+
+            if self.starts_at.is_some_and(|s| now < s) || self.ends_at.is_some_and(|e| now >= e) {
+                return Ok(false);
+            }
+        }
+
+        // all short-circuits on the first false result and any on the first true result
+        let short_circuit = self.combinator == Combinator::Any;
+        let mut matched = !short_circuit;
+        let mut bound: Option<Environment> = None;
+        for expr in &self.expressions {
+            // calls are bound just before the expression that makes them, so skipped expressions make no calls
+            if !expr.calls.is_empty() {
+                let bound = bound.get_or_insert_with(|| ctx.environment());
+                for call in &expr.calls {
+                    bound.set(&call.variable, call.evaluate(ctx)?);
+                }
+            }
+
+            let result = mexl::run(&expr.program, bound.as_ref().unwrap_or(env)).map_err(|e| {
+                FetaError::Targeting(e.to_string())
+                    .with_context(&format!("[expr: {}]", expr.source))
+            })?;
+
+            if (result == true.into()) == short_circuit {
+                matched = short_circuit;
+                break;
             }
-            None => Ok(true),
         }
+
+        Ok(matched != self.negate)
     }
 
-    /// Determines the variant for the given hash value based on the rule's bucket configuration.
-    pub fn get_variant(&self, hash: u32) -> String {
-        let hash_mod = hash % 100_u32;
+    /// Determines the variant for the given hash value based on the rule's bucket configuration, returning `None` if the hash falls outside of all buckets.
+    ///
+    /// The variant is borrowed from the rule, so no allocation is made when evaluating features.
+    pub fn get_variant(&self, hash: u32) -> Option<&str> {
+        let bucket = hash::to_bucket(hash);
         self.buckets
             .iter()
-            .find(|b| hash_mod >= b.lower_bound && hash_mod < b.upper_bound)
-            .unwrap() // this is unreachable if constructed via builder
-            .variant
-            .clone()
+            .find(|b| bucket >= b.lower_bound && bucket < b.upper_bound)
+            .map(|b| b.variant.as_str())
+    }
+
+    /// Returns the variant and bucket range for each bucket in the rule, where the lower bound is inclusive and the upper bound is exclusive.
+    ///
+    /// Bounds are expressed in buckets out of 10,000, so each percent of a distribution spans 100 buckets.
+    pub fn bucket_ranges(&self) -> Vec<(String, u32, u32)> {
+        self.buckets
+            .iter()
+            .map(|b| (b.variant.clone(), b.lower_bound, b.upper_bound))
+            .collect()
+    }
+
+    /// Returns the audience name for the rule, if one exists.
+    pub fn audience(&self) -> Option<&str> {
+        self.audience.as_deref()
+    }
+
+    /// Returns the original audience expressions for the rule, in evaluation order.
+    pub fn expressions(&self) -> impl Iterator<Item = &str> + '_ {
+        self.expressions.iter().map(|e| e.source.as_str())
+    }
+
+    /// Returns true if the rule has at least one audience expression.
+    pub(crate) fn has_expression(&self) -> bool {
+        !self.expressions.is_empty()
+    }
+
+    /// Returns true if the rule has a start or end timestamp.
+    pub(crate) fn is_scheduled(&self) -> bool {
+        self.starts_at.is_some() || self.ends_at.is_some()
+    }
+
+    /// Returns true if the rule buckets cover less than 100 percent, allowing evaluation to fall through to the next rule.
+    pub(crate) fn is_partial(&self) -> bool {
+        self.buckets
+            .last()
+            .is_none_or(|b| b.upper_bound < BUCKET_COUNT)
+    }
+
+    /// Reconstructs the bucketing configuration for the rule.
+    pub(crate) fn to_bucketing(&self) -> config::Bucketing {
+        match self.buckets.as_slice() {
+            [b] if b.lower_bound == 0 && b.upper_bound == BUCKET_COUNT => {
+                config::Bucketing::Variant {
+                    variant: b.variant.clone(),
+                }
+            }
+            [b] if b.lower_bound == 0 => config::Bucketing::Rollout {
+                variant: b.variant.clone(),
+                percentage: b.upper_bound as f64 / (BUCKET_COUNT / 100) as f64,
+            },
+            buckets => config::Bucketing::Distribution {
+                distribution: buckets
+                    .iter()
+                    .map(|b| {
+                        let percentage =
+                            (b.upper_bound - b.lower_bound) as f64 / (BUCKET_COUNT / 100) as f64;
+                        (b.variant.clone(), percentage)
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    /// Reconstructs the audience rule configuration, returning `None` if the rule has no audience.
+    pub(crate) fn to_audience_rule(&self) -> Option<config::AudienceRule> {
+        let name = self.audience.clone()?;
+
+        let mut sources: Vec<String> = self.expressions().map(String::from).collect();
+        let expression = match self.combinator {
+            Combinator::All if sources.len() == 1 => config::Expression::Single {
+                expression: sources.remove(0),
+            },
+            Combinator::All => config::Expression::All { all: sources },
+            Combinator::Any => config::Expression::Any { any: sources },
+        };
+
+        Some(config::AudienceRule {
+            name,
+            expression,
+            negate: self.negate,
+            priority: self.priority,
+            starts_at: self.starts_at.map(|t| t.to_rfc3339()),
+            ends_at: self.ends_at.map(|t| t.to_rfc3339()),
+            bucketing: self.to_bucketing(),
+            normalize: false,
+        })
     }
 
     /// Returns an iterator over the variants that are referenced by this rule.
@@ -139,6 +575,10 @@ impl Rule {
 mod tests {
     use super::*;
 
+    fn context(attributes: serde_json::Value) -> Context {
+        Context::with_json_attributes("a", attributes).expect("context should build")
+    }
+
     #[test]
     fn test_rule_builder_default_static() {
         let rule = RuleBuilder::default()
@@ -147,6 +587,7 @@ mod tests {
             .expect("rule should build");
 
         assert_eq!(rule.reason, Reason::Static);
+        assert!(!rule.has_expression());
     }
 
     #[test]
@@ -169,7 +610,41 @@ mod tests {
             .expect("rule should build");
 
         assert_eq!(rule.reason, Reason::Match);
-        assert_eq!(rule.audience.unwrap_or_default(), "beta");
+        assert_eq!(rule.audience(), Some("beta"));
+        assert_eq!(rule.expressions().collect::<Vec<_>>(), vec!["orders gt 10"]);
+    }
+
+    #[test]
+    fn test_rule_builder_audience_boolean() {
+        for expr in ["orders gt 10", "true", "is_beta"] {
+            let rule = RuleBuilder::default()
+                .variant("a", 100)
+                .audience("beta", expr)
+                .build();
+
+            assert!(rule.is_ok(), "expression: {}", expr);
+        }
+    }
+
+    #[test]
+    fn test_rule_builder_audience_arithmetic() {
+        let build = |expr| {
+            RuleBuilder::default()
+                .variant("a", 100)
+                .audience("beta", expr)
+                .build()
+        };
+
+        assert!(matches!(build("1 + 2"), Err(FetaError::Targeting(_))));
+
+        // arithmetic over attributes cannot be checked without values, so it is only found at evaluation
+        let rule = build("orders + 1").expect("rule should build");
+        let ctx = context(serde_json::json!({"orders": 10}));
+        assert!(
+            !rule
+                .is_applicable(&ctx, None)
+                .expect("rule should evaluate")
+        );
     }
 
     #[test]
@@ -184,15 +659,151 @@ mod tests {
         assert_eq!(rule.reason, Reason::MatchSplit)
     }
 
+    #[test]
+    fn test_rule_builder_as_default() {
+        let rule = RuleBuilder::new()
+            .variant("a", 50)
+            .variant("b", 50)
+            .as_default()
+            .build()
+            .expect("rule should build");
+
+        assert!(rule.is_default);
+        assert_eq!(rule.reason, Reason::Split);
+
+        let err = RuleBuilder::new()
+            .variant("a", 100)
+            .audience("beta", "is_beta")
+            .as_default()
+            .build()
+            .err();
+
+        assert_eq!(
+            err,
+            Some(FetaError::Configuration(
+                "audience not permitted for default rule".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rule_builder_three_way_split() {
+        let rule = RuleBuilder::new()
+            .variant("a", 34)
+            .variant("b", 33)
+            .variant("c", 33)
+            .build()
+            .expect("rule should build");
+
+        assert_eq!(rule.reason, Reason::Split);
+        assert!(!rule.is_partial());
+    }
+
+    #[test]
+    fn test_rule_builder_normalize() {
+        let rule = RuleBuilder::new()
+            .distribution([("a", 1), ("b", 1), ("c", 1)])
+            .normalize(true)
+            .as_default()
+            .build()
+            .expect("rule should build");
+
+        let expected = vec![
+            ("a".to_string(), 0, 3333),
+            ("b".to_string(), 3333, 6667),
+            ("c".to_string(), 6667, 10_000),
+        ];
+        assert_eq!(rule.bucket_ranges(), expected);
+        assert!(!rule.is_partial());
+
+        let counts = (0..30_000).fold([0; 3], |mut counts, i| {
+            let hash = hash::calculate("exp", &format!("user{}", i));
+            let variant = rule.get_variant(hash).expect("variant should exist");
+            counts[variant.as_bytes()[0] as usize - b'a' as usize] += 1;
+            counts
+        });
+        for count in counts {
+            assert!((9_500..10_500).contains(&count), "count: {}", count);
+        }
+
+        let rule = RuleBuilder::new()
+            .distribution([("a", 50), ("b", 49)])
+            .normalize(true)
+            .as_default()
+            .build()
+            .expect("rule should build");
+        assert!(!rule.is_partial());
+
+        let tests = vec![
+            RuleBuilder::new().variant("a", 0), // weights total zero
+            RuleBuilder::new().variant("a", 1).variant("b", -1), // negative weight
+            RuleBuilder::new().variant("a", f64::NAN), // non-finite weight
+        ];
+
+        for test in tests {
+            assert!(test.normalize(true).build().is_err());
+        }
+
+        let result = RuleBuilder::new()
+            .distribution([("a", 50), ("b", 49)])
+            .as_default()
+            .build();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_rule_builder_errors() {
         let tests = vec![
-            RuleBuilder::new() // rule with invalid percentages
+            RuleBuilder::new() // rule with no variants
+                .audience("audience", "true"),
+            RuleBuilder::new() // rule with percentages over 100
                 .variant("a", 50)
-                .variant("b", 40),
+                .variant("b", 60),
+            RuleBuilder::new() // rule with fractional percentages over 100
+                .variant("a", 50.005)
+                .variant("b", 50),
+            RuleBuilder::new() // rule with percentage below 0.01
+                .variant("a", 0.001),
+            RuleBuilder::new() // rule with negative percentage
+                .variant("a", -1),
+            RuleBuilder::new() // rule with empty variant name
+                .variant("", 100),
+            RuleBuilder::new() // rule with duplicate variants
+                .variant("a", 50)
+                .variant("a", 50),
             RuleBuilder::new() // rule with invalid expression
                 .variant("a", 100)
                 .audience("audience", "+2"), // mexl compile error
+            RuleBuilder::new() // rule with empty expressions
+                .variant("a", 100)
+                .audience_all("audience", Vec::<String>::new()),
+            RuleBuilder::new() // rule with one invalid expression of many
+                .variant("a", 100)
+                .audience_any("audience", ["true", "+2"]),
+            RuleBuilder::new() // rule with non-boolean expression
+                .variant("a", 100)
+                .audience("audience", "1 + 2"),
+            RuleBuilder::new() // negated rule without audience
+                .variant("a", 100)
+                .negate(true),
+            RuleBuilder::new() // default rule with audience
+                .variant("a", 100)
+                .audience("audience", "true")
+                .as_default(),
+            RuleBuilder::new() // scheduled default rule
+                .variant("a", 100)
+                .starts_at("2025-01-01T00:00:00Z")
+                .as_default(),
+            RuleBuilder::new() // partial default rule
+                .variant("a", 50)
+                .as_default(),
+            RuleBuilder::new() // invalid timestamp
+                .variant("a", 100)
+                .starts_at("2025-01-01"),
+            RuleBuilder::new() // rule ends before it starts
+                .variant("a", 100)
+                .starts_at("2025-02-01T00:00:00Z")
+                .ends_at("2025-01-01T00:00:00Z"),
         ];
 
         for test in tests {
@@ -205,7 +816,7 @@ mod tests {
     fn test_rule_is_applicable() {
         struct TestCase {
             builder: RuleBuilder,
-            environment: Environment,
+            context: Context,
             expected: Result<bool, ()>,
         }
 
@@ -213,19 +824,19 @@ mod tests {
             TestCase {
                 // no expression evaluates to true
                 builder: RuleBuilder::new().variant("a", 100),
-                environment: Environment::default(),
+                context: Context::new("a"),
                 expected: Ok(true),
             },
             TestCase {
                 // expression evaluates to true
                 builder: RuleBuilder::new().variant("a", 100).audience("beta", "b"),
-                environment: serde_json::from_str(r#"{"b": true}"#).unwrap(),
+                context: context(serde_json::json!({"b": true})),
                 expected: Ok(true),
             },
             TestCase {
                 // expression evaluates to false
                 builder: RuleBuilder::new().variant("a", 100).audience("beta", "b"),
-                environment: serde_json::from_str(r#"{"b": false}"#).unwrap(),
+                context: context(serde_json::json!({"b": false})),
                 expected: Ok(false),
             },
             TestCase {
@@ -233,14 +844,14 @@ mod tests {
                 builder: RuleBuilder::new()
                     .variant("a", 100)
                     .audience("beta", "true.a"),
-                environment: Environment::default(),
+                context: Context::new("a"),
                 expected: Err(()),
             },
         ];
 
         for test in tests {
             let rule = test.builder.build().expect("rule should build");
-            let result = rule.is_applicable(&test.environment);
+            let result = rule.is_applicable(&test.context, None);
             assert_eq!(result.is_err(), test.expected.is_err());
 
             if let Ok(actual) = result {
@@ -249,6 +860,192 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rule_is_applicable_combined() {
+        let ctx = context(serde_json::json!({"a": true, "b": false}));
+
+        let tests = vec![
+            (RuleBuilder::new().audience_all("beta", ["a", "a"]), true),
+            (RuleBuilder::new().audience_all("beta", ["a", "b"]), false),
+            (RuleBuilder::new().audience_any("beta", ["b", "a"]), true),
+            (RuleBuilder::new().audience_any("beta", ["b", "b"]), false),
+            // evaluation short-circuits before the runtime error
+            (
+                RuleBuilder::new().audience_all("beta", ["b", "true.a"]),
+                false,
+            ),
+            (
+                RuleBuilder::new().audience_any("beta", ["a", "true.a"]),
+                true,
+            ),
+        ];
+
+        for (builder, expected) in tests {
+            let rule = builder
+                .variant("a", 100)
+                .build()
+                .expect("rule should build");
+            let actual = rule.is_applicable(&ctx, None).expect("should evaluate");
+            assert_eq!(
+                actual,
+                expected,
+                "{:?}",
+                rule.expressions().collect::<Vec<_>>()
+            );
+        }
+
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience_all("beta", ["a", "true.a"])
+            .build()
+            .expect("rule should build");
+
+        let err = rule.is_applicable(&ctx, None).expect_err("should fail");
+        assert!(err.to_string().contains("[expr: true.a]"), "{}", err);
+    }
+
+    #[test]
+    fn test_rule_is_applicable_calls() {
+        let ctx = context(serde_json::json!({"roles": ["admin"], "a": true, "b": false}));
+
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience("admins", r#"contains(roles, "admin")"#)
+            .build()
+            .expect("rule should build");
+
+        assert!(rule.is_applicable(&ctx, None).expect("should evaluate"));
+
+        // calls are only made for the expressions that are evaluated
+        let functions = Functions::new()
+            .with_function("fail", |_| Err(FetaError::Targeting("failed".to_string())));
+
+        let tests = vec![
+            (
+                RuleBuilder::new().audience_all("beta", ["b", "fail(a)"]),
+                false,
+            ),
+            (
+                RuleBuilder::new().audience_any("beta", ["a", "fail(a)"]),
+                true,
+            ),
+        ];
+
+        for (builder, expected) in tests {
+            let rule = builder
+                .variant("a", 100)
+                .functions(functions.clone())
+                .build()
+                .expect("rule should build");
+            let actual = rule.is_applicable(&ctx, None).expect("should evaluate");
+            assert_eq!(actual, expected);
+        }
+
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience_all("beta", ["a", "fail(a)"])
+            .functions(functions)
+            .build()
+            .expect("rule should build");
+
+        let err = rule.is_applicable(&ctx, None).expect_err("should fail");
+        assert!(err.to_string().contains("[call: fail]"), "{}", err);
+    }
+
+    #[test]
+    fn test_rule_is_applicable_negated() {
+        let ctx = context(serde_json::json!({"a": true, "b": false}));
+
+        let tests = vec![
+            (RuleBuilder::new().audience("internal", "a"), false),
+            (RuleBuilder::new().audience("internal", "b"), true),
+            (
+                RuleBuilder::new().audience_any("internal", ["b", "a"]),
+                false,
+            ),
+            (
+                RuleBuilder::new().audience_all("internal", ["a", "b"]),
+                true,
+            ),
+        ];
+
+        for (builder, expected) in tests {
+            let rule = builder
+                .variant("a", 100)
+                .negate(true)
+                .build()
+                .expect("rule should build");
+
+            let actual = rule.is_applicable(&ctx, None).expect("should evaluate");
+            assert_eq!(
+                actual,
+                expected,
+                "{:?}",
+                rule.expressions().collect::<Vec<_>>()
+            );
+        }
+
+        // the schedule is not negated
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience("internal", "b")
+            .negate(true)
+            .starts_at("2025-01-01T00:00:00Z")
+            .build()
+            .expect("rule should build");
+
+        assert!(!rule.is_applicable(&ctx, None).unwrap());
+    }
+
+    #[test]
+    fn test_rule_is_applicable_scheduled() {
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience("beta", "b")
+            .starts_at("2025-01-01T00:00:00Z")
+            .ends_at("2025-02-01T00:00:00+01:00")
+            .build()
+            .expect("rule should build");
+
+        let ctx = context(serde_json::json!({"b": true}));
+
+        let tests = vec![
+            (None, false),
+            (Some("2024-12-31T23:59:59Z"), false),
+            (Some("2025-01-01T00:00:00Z"), true),
+            (Some("2025-01-15T12:00:00-05:00"), true),
+            (Some("2025-01-31T23:00:00Z"), false),
+            (Some("2025-03-01T00:00:00Z"), false),
+        ];
+
+        for (now, expected) in tests {
+            let now = now.map(|n| parse_timestamp(n).unwrap());
+            let actual = rule.is_applicable(&ctx, now).expect("should evaluate");
+            assert_eq!(actual, expected, "{:?}", now);
+        }
+
+        // the expression is still evaluated within the schedule
+        let now = parse_timestamp("2025-01-15T00:00:00Z").ok();
+        let ctx = context(serde_json::json!({"b": false}));
+        assert!(!rule.is_applicable(&ctx, now).unwrap());
+    }
+
+    #[test]
+    fn test_rule_debug() {
+        let rule = RuleBuilder::new()
+            .variant("a", 100)
+            .audience("beta", "orders gt 10")
+            .build()
+            .expect("rule should build");
+
+        let actual = format!("{:?}", rule);
+        assert!(
+            actual.contains(r#"Expression { source: "orders gt 10", .. }"#),
+            "{}",
+            actual
+        );
+    }
+
     #[test]
     fn test_rule_get_variant() {
         let rule = RuleBuilder::new()
@@ -261,8 +1058,94 @@ mod tests {
 
         for (hash, expected) in tests {
             let actual = rule.get_variant(hash);
-            assert_eq!(actual, expected.to_string());
+            assert_eq!(actual, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_rule_get_variant_partial() {
+        let rule = RuleBuilder::new()
+            .variant("a", 10)
+            .build()
+            .expect("rule should build");
+
+        let tests = vec![
+            (0, Some("a")),
+            (9, Some("a")),
+            (10, None),
+            (99, None),
+            (100, Some("a")),
+        ];
+
+        for (hash, expected) in tests {
+            let actual = rule.get_variant(hash);
+            assert_eq!(actual, expected);
         }
+
+        assert!(rule.is_partial());
+    }
+
+    #[test]
+    fn test_rule_get_variant_fractional() {
+        let rule = RuleBuilder::new()
+            .variant("a", 33.33)
+            .variant("b", 33.33)
+            .variant("c", 33.34)
+            .build()
+            .expect("rule should build");
+
+        assert!(!rule.is_partial());
+
+        let rule = RuleBuilder::new()
+            .variant("a", 0.5)
+            .build()
+            .expect("rule should build");
+
+        let count = (0..100_000)
+            .filter(|i| {
+                let hash = hash::calculate("feature", &format!("user{}", i));
+                rule.get_variant(hash).is_some()
+            })
+            .count();
+
+        // 0.5% of 100,000 users is 500
+        assert!((400..600).contains(&count), "count: {}", count);
+
+        let rule = RuleBuilder::new()
+            .variant("a", 0.1)
+            .variant("b", 99.9)
+            .build()
+            .expect("rule should build");
+
+        assert_eq!(rule.bucket_ranges()[0], ("a".to_string(), 0, 10));
+
+        let count = (0..100_000)
+            .filter(|i| {
+                let hash = hash::calculate("feature", &format!("user{}", i));
+                rule.get_variant(hash).is_some_and(|v| v == "a")
+            })
+            .count();
+
+        // 0.1% of 100,000 users is 100
+        assert!((50..150).contains(&count), "count: {}", count);
+    }
+
+    #[test]
+    fn test_rule_bucket_ranges() {
+        let rule = RuleBuilder::new()
+            .variant("a", 34)
+            .variant("b", 33)
+            .variant("c", 33)
+            .build()
+            .expect("rule should build");
+
+        let expected = vec![
+            ("a".to_string(), 0, 3400),
+            ("b".to_string(), 3400, 6700),
+            ("c".to_string(), 6700, 10_000),
+        ];
+
+        assert_eq!(rule.bucket_ranges(), expected);
     }
 
     #[test]
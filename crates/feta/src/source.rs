@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use arc_swap::ArcSwap;
+
+use crate::{
+    Context, Decision, Evaluator, Features, FeaturesBuilder, config::Config, error::FetaError,
+};
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisConfigSource;
+
+/// A source from which configuration can be loaded, such as a file or a remote service.
+pub trait ConfigSource {
+    /// Loads the current configuration from the source.
+    fn load(&self) -> Result<Config, FetaError>;
+}
+
+/// A `ConfigSource` that reads JSON configuration from a file.
+#[derive(Debug, Clone)]
+pub struct FileConfigSource {
+    path: PathBuf,
+}
+
+impl FileConfigSource {
+    /// Creates a new `FileConfigSource` for the file at the specified path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ConfigSource for FileConfigSource {
+    /// Reads and deserializes the configuration file.
+    fn load(&self) -> Result<Config, FetaError> {
+        let json = fs::read_to_string(&self.path).map_err(|e| {
+            FetaError::Configuration(format!("failed to read {}: {}", self.path.display(), e))
+        })?;
+
+        Config::from_json(&json)
+    }
+}
+
+/// A shared handle to a `Features` registry that can be reloaded from a `ConfigSource` while it is in use.
+///
+/// Clones share the same registry, and evaluations that are in progress during a reload complete against the previous features.
+/// Reloads retain the variables, functions, and observer that the features were built with.
+#[derive(Clone)]
+pub struct FeaturesHandle {
+    source: Arc<dyn ConfigSource + Send + Sync>,
+    features: Arc<ArcSwap<Features>>,
+}
+
+impl FeaturesHandle {
+    /// Creates a new `FeaturesHandle`, loading the initial features from the specified source.
+    pub fn new(source: impl ConfigSource + Send + Sync + 'static) -> Result<Self, FetaError> {
+        Self::with_builder(source, FeaturesBuilder::new())
+    }
+
+    /// Creates a new `FeaturesHandle`, loading the initial features from the specified source with the builder.
+    pub fn with_builder(
+        source: impl ConfigSource + Send + Sync + 'static,
+        builder: FeaturesBuilder,
+    ) -> Result<Self, FetaError> {
+        let features = builder.build(&source.load()?)?;
+
+        Ok(Self {
+            source: Arc::new(source),
+            features: Arc::new(ArcSwap::from_pointee(features)),
+        })
+    }
+
+    /// Reloads the features from the source, leaving the current features in place if the configuration cannot be loaded or is invalid.
+    pub fn reload(&self) -> Result<(), FetaError> {
+        self.update_from_config(&self.source.load()?)
+    }
+
+    /// Replaces the features with those from the given configuration, leaving the current features in place if it is invalid.
+    ///
+    /// The new features are built before they are swapped in, so evaluations are not blocked while the configuration is compiled.
+    pub fn update_from_config(&self, cfg: &Config) -> Result<(), FetaError> {
+        let features = self.features.load().rebuild(cfg)?;
+        self.features.store(Arc::new(features));
+        Ok(())
+    }
+
+    /// Reloads the features from the source at the specified interval on a background thread, until the returned `Watcher` is stopped.
+    ///
+    /// Reload errors are passed to `on_error` and leave the current features in place.
+    pub fn poll(
+        &self,
+        interval: Duration,
+        on_error: impl Fn(FetaError) + Send + 'static,
+    ) -> Watcher {
+        let handle = self.clone();
+        Watcher::spawn(move |stopped| {
+            loop {
+                thread::park_timeout(interval);
+                if stopped.load(Ordering::Acquire) {
+                    return Ok(());
+                }
+
+                if let Err(e) = handle.reload() {
+                    on_error(e);
+                }
+            }
+        })
+    }
+
+    /// Returns a snapshot of the current features, which is unaffected by subsequent reloads.
+    pub fn features(&self) -> Arc<Features> {
+        self.features.load_full()
+    }
+
+    /// Evaluates the specified feature for the given context using the current features.
+    pub fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        self.features.load().decide(feature, ctx)
+    }
+
+    /// Evaluates all features for the given context using the current features.
+    pub fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        self.features.load().decide_all(ctx)
+    }
+}
+
+/// A background thread that reloads a `FeaturesHandle`, which is signalled to stop when it is dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<(), FetaError>>>,
+}
+
+impl Watcher {
+    /// Spawns the thread, passing it the flag that is set when the watcher is stopped.
+    pub(crate) fn spawn(
+        f: impl FnOnce(Arc<AtomicBool>) -> Result<(), FetaError> + Send + 'static,
+    ) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+
+        Self {
+            stopped,
+            thread: Some(thread::spawn(move || f(flag))),
+        }
+    }
+
+    /// Returns true if the thread has exited, either because it was stopped or because it failed.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(|t| t.is_finished())
+    }
+
+    /// Stops the thread and waits for it to exit, returning the error if it exited because it failed.
+    pub fn stop(mut self) -> Result<(), FetaError> {
+        self.signal();
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(FetaError::Configuration(
+                "watcher thread panicked".to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets the stop flag and wakes the thread if it is waiting for the next poll.
+    fn signal(&self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(thread) = &self.thread {
+            thread.thread().unpark();
+        }
+    }
+}
+
+impl Drop for Watcher {
+    /// Signals the thread to stop without waiting for it to exit.
+    fn drop(&mut self) {
+        self.signal();
+    }
+}
+
+impl Evaluator for FeaturesHandle {
+    fn decide(&self, feature: &str, ctx: &Context) -> Decision {
+        FeaturesHandle::decide(self, feature, ctx)
+    }
+
+    fn decide_all(&self, ctx: &Context) -> HashMap<String, Decision> {
+        FeaturesHandle::decide_all(self, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reason;
+
+    const CONFIG: &str = r#"{
+        "features": {
+            "f1": {
+                "enabled": true,
+                "value_type": "integer",
+                "variants": {"a": 1, "b": 2},
+                "default_variant": "a",
+                "default_rule": {"variant": "a"}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_file_config_source() {
+        let path = std::env::temp_dir().join("feta_test_file_config_source.json");
+        fs::write(&path, CONFIG).unwrap();
+
+        let actual = FileConfigSource::new(&path).load().expect("should load");
+        assert!(actual.features.contains_key("f1"));
+
+        fs::write(&path, "{").unwrap();
+        assert!(FileConfigSource::new(&path).load().is_err());
+
+        fs::remove_file(&path).unwrap();
+        assert!(FileConfigSource::new(&path).load().is_err());
+    }
+
+    #[test]
+    fn test_features_handle_reload() {
+        let path = std::env::temp_dir().join("feta_test_features_handle_reload.json");
+        fs::write(&path, CONFIG).unwrap();
+
+        let handle = FeaturesHandle::new(FileConfigSource::new(&path)).expect("should load");
+        let snapshot = handle.clone().features();
+        let ctx = Context::new("g");
+
+        assert_eq!(handle.decide("f1", &ctx).variant, "a");
+
+        // invalid configuration is not swapped in
+        fs::write(
+            &path,
+            CONFIG.replace(r#""default_variant": "a""#, r#""default_variant": "c""#),
+        )
+        .unwrap();
+        assert!(handle.reload().is_err());
+        assert_eq!(handle.decide("f1", &ctx).variant, "a");
+
+        fs::write(
+            &path,
+            CONFIG.replace(r#"{"variant": "a"}"#, r#"{"variant": "b"}"#),
+        )
+        .unwrap();
+        handle.reload().expect("should reload");
+        assert_eq!(handle.decide("f1", &ctx).variant, "b");
+        assert_eq!(handle.decide_all(&ctx)["f1"].reason, Reason::Static);
+
+        // snapshots are unaffected by reloads
+        assert_eq!(snapshot.decide("f1", &ctx).variant, "a");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_features_handle_with_builder() {
+        let path = std::env::temp_dir().join("feta_test_features_handle_with_builder.json");
+        fs::write(
+            &path,
+            r#"{
+                "features": {
+                    "f1": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "default_variant": "off",
+                        "audience_rules": [
+                            { "name": "beta", "expression": "is_beta(tier, \"${TIER}\")", "variant": "on" }
+                        ],
+                        "default_rule": { "variant": "off" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let functions = crate::Functions::new().with_function("is_beta", |args| {
+            Ok(crate::Object::Boolean(args.first() == args.get(1)))
+        });
+        let builder = FeaturesBuilder::new()
+            .vars(HashMap::from([("TIER".to_string(), "beta".to_string())]))
+            .functions(functions);
+
+        let handle = FeaturesHandle::with_builder(FileConfigSource::new(&path), builder)
+            .expect("should load");
+        let ctx = crate::ContextBuilder::new()
+            .user_key("a")
+            .string_attribute("tier", "beta")
+            .build()
+            .unwrap();
+
+        assert_eq!(handle.decide("f1", &ctx).variant, "on");
+
+        // the variables and functions are retained on reload
+        handle.reload().expect("should reload");
+        assert_eq!(handle.decide("f1", &ctx).variant, "on");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_features_handle_poll() {
+        let path = std::env::temp_dir().join("feta_test_features_handle_poll.json");
+        fs::write(&path, CONFIG).unwrap();
+
+        let handle = FeaturesHandle::new(FileConfigSource::new(&path)).expect("should load");
+        let ctx = Context::new("g");
+
+        let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let on_error = {
+            let errors = errors.clone();
+            move |e| errors.lock().unwrap().push(e)
+        };
+        let watcher = handle.poll(Duration::from_millis(10), on_error);
+
+        fs::write(
+            &path,
+            CONFIG.replace(r#"{"variant": "a"}"#, r#"{"variant": "b"}"#),
+        )
+        .unwrap();
+
+        let reloaded = (0..200).any(|_| {
+            thread::sleep(Duration::from_millis(10));
+            handle.decide("f1", &ctx).variant == "b"
+        });
+        assert!(reloaded);
+
+        watcher.stop().expect("should stop");
+        assert!(errors.lock().unwrap().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,116 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use redis::{Client, Commands};
+
+use crate::{
+    config::Config,
+    error::FetaError,
+    source::{ConfigSource, FeaturesHandle, Watcher},
+};
+
+/// The read timeout for the subscription, which is how often the thread checks whether it has been stopped.
+const STOP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A `ConfigSource` that reads JSON configuration from a Redis key.
+#[derive(Debug, Clone)]
+pub struct RedisConfigSource {
+    client: Client,
+    key: String,
+}
+
+impl RedisConfigSource {
+    /// Creates a new `RedisConfigSource` for the specified connection URL and key.
+    ///
+    /// The URL is validated, but a connection is not made until the configuration is loaded.
+    pub fn new(url: &str, key: impl Into<String>) -> Result<Self, FetaError> {
+        let client = Client::open(url).map_err(to_error)?;
+
+        Ok(Self {
+            client,
+            key: key.into(),
+        })
+    }
+
+    /// Subscribes to the specified channel on a background thread, loading the configuration from this source's key into the
+    /// handle when a message is published, until the returned `Watcher` is stopped.
+    ///
+    /// The subscription is established before returning, so updates published after this call are not missed.
+    /// Update errors are passed to `on_error` and leave the current features in place, while the thread exits
+    /// with the error if the subscription connection fails. A stopped thread exits within one second.
+    pub fn subscribe(
+        &self,
+        channel: &str,
+        handle: FeaturesHandle,
+        on_error: impl Fn(FetaError) + Send + 'static,
+    ) -> Result<Watcher, FetaError> {
+        let mut conn = self.client.get_connection().map_err(to_error)?;
+        conn.set_read_timeout(Some(STOP_INTERVAL))
+            .map_err(to_error)?;
+        conn.as_pubsub().subscribe(channel).map_err(to_error)?;
+
+        let source = self.clone();
+        Ok(Watcher::spawn(move |stopped| {
+            let mut pubsub = conn.as_pubsub();
+            while !stopped.load(Ordering::Acquire) {
+                // the message payload is ignored, as the configuration is always loaded from the key
+                match pubsub.get_message() {
+                    Ok(_) => {}
+                    Err(e) if e.is_timeout() => continue,
+                    Err(e) => return Err(to_error(e)),
+                }
+
+                if let Err(e) = source
+                    .load()
+                    .and_then(|cfg| handle.update_from_config(&cfg))
+                {
+                    on_error(e);
+                }
+            }
+
+            Ok(())
+        }))
+    }
+}
+
+impl ConfigSource for RedisConfigSource {
+    /// Reads and deserializes the configuration from the key.
+    fn load(&self) -> Result<Config, FetaError> {
+        let mut conn = self.client.get_connection().map_err(to_error)?;
+
+        let json: Option<String> = conn.get(&self.key).map_err(to_error)?;
+        let json = json.ok_or_else(|| {
+            FetaError::Configuration(format!("redis key does not exist: {}", self.key))
+        })?;
+
+        Config::from_json(&json)
+    }
+}
+
+/// Converts a Redis error into a `FetaError::Configuration`.
+fn to_error(err: redis::RedisError) -> FetaError {
+    FetaError::Configuration(format!("redis error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redis_config_source() {
+        assert!(RedisConfigSource::new("invalid", "feta").is_err());
+
+        // nothing listens on port 1, so the connection is refused without a timeout
+        let source = RedisConfigSource::new("redis://127.0.0.1:1/", "feta").expect("url is valid");
+        let err = source.load().expect_err("connection should fail");
+        assert!(matches!(err, FetaError::Configuration(_)));
+
+        assert!(FeaturesHandle::new(source.clone()).is_err());
+
+        let path = std::env::temp_dir().join("feta_test_redis_config_source.json");
+        std::fs::write(&path, r#"{"features": {}}"#).unwrap();
+        let handle = FeaturesHandle::new(crate::FileConfigSource::new(&path)).expect("should load");
+        assert!(source.subscribe("feta", handle, |_| {}).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,8 +1,13 @@
-use std::fmt;
+use std::{cmp::Ordering, collections::BTreeMap, fmt, str::FromStr};
 
-use serde::{Deserialize, Serialize};
+use serde::{
+    Deserialize, Serialize,
+    de::{IntoDeserializer, value::Error},
+};
 
-/// The type of a feature value, which can be an integer, float, boolean, or string.
+use crate::error::FetaError;
+
+/// The type of a feature value, which can be an integer, float, boolean, string, array, or object.
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ValueType {
@@ -14,6 +19,8 @@ pub enum ValueType {
     Boolean,
     #[serde(alias = "string")]
     String,
+    Array,
+    Object,
 }
 
 impl fmt::Display for ValueType {
@@ -24,11 +31,24 @@ impl fmt::Display for ValueType {
             Self::Float => f.write_str("float"),
             Self::Boolean => f.write_str("boolean"),
             Self::String => f.write_str("string"),
+            Self::Array => f.write_str("array"),
+            Self::Object => f.write_str("object"),
         }
     }
 }
 
-/// The value of a feature variant, which can be null, an integer, a float, a boolean, or a string.
+impl FromStr for ValueType {
+    type Err = FetaError;
+
+    /// Parses a `ValueType` from a string, accepting the same aliases as deserialization, such as `int` and `bool`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // deserializing from the string keeps the accepted aliases consistent with config parsing
+        Self::deserialize(IntoDeserializer::<Error>::into_deserializer(s))
+            .map_err(|_| FetaError::Request(format!("unknown value type: {}", s)))
+    }
+}
+
+/// The value of a feature variant, which can be null, an integer, a float, a boolean, a string, an array, or an object.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Value {
@@ -37,6 +57,8 @@ pub enum Value {
     Float(f64),
     Boolean(bool),
     String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
 }
 
 impl Value {
@@ -48,8 +70,94 @@ impl Value {
                 | (Value::Float(_), ValueType::Float)
                 | (Value::Boolean(_), ValueType::Boolean)
                 | (Value::String(_), ValueType::String)
+                | (Value::Array(_), ValueType::Array)
+                | (Value::Object(_), ValueType::Object)
         )
     }
+
+    /// Checks if every element of an array `Value` has the specified `ValueType`, returning false for non-array values.
+    pub(crate) fn has_element_type(&self, t: &ValueType) -> bool {
+        match self {
+            Value::Array(items) => items.iter().all(|v| v.has_type(t)),
+            _ => false,
+        }
+    }
+
+    /// Checks if the `Value` contains only finite floats, including any nested array elements or object values.
+    pub(crate) fn is_finite(&self) -> bool {
+        match self {
+            Value::Float(f) => f.is_finite(),
+            Value::Array(items) => items.iter().all(Value::is_finite),
+            Value::Object(map) => map.values().all(Value::is_finite),
+            _ => true,
+        }
+    }
+
+    /// Returns the value as an `i64` if it is an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `f64` if it is a float, widening integers.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(f) => Some(*f),
+            Value::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Compares numeric values, widening integers to floats, and returns `None` if either value is not a number.
+    ///
+    /// Unlike `partial_cmp`, this returns `Equal` for `Integer(1)` and `Float(1.0)`.
+    pub fn compare_numeric(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
+            _ => self.as_f64()?.partial_cmp(&other.as_f64()?),
+        }
+    }
+
+    /// Returns the value as a `bool` if it is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a string slice if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    /// Compares values of the same type, returning `None` for values of different types.
+    ///
+    /// Integers are widened to floats when compared with floats, but values that are equal once widened return `None`,
+    /// as `Integer(1)` and `Float(1.0)` are not `==`; use `Value::compare_numeric` to treat them as equal. Arrays are
+    /// compared lexicographically, and objects are only comparable if they are equal.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, Value::Null) => Some(Ordering::Equal),
+            (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Integer(_), Value::Float(_)) | (Value::Float(_), Value::Integer(_)) => self
+                .compare_numeric(other)
+                .filter(|o| *o != Ordering::Equal),
+            (Value::Boolean(a), Value::Boolean(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.partial_cmp(b),
+            (Value::Object(a), Value::Object(b)) => (a == b).then_some(Ordering::Equal),
+            _ => None,
+        }
+    }
 }
 
 impl From<i64> for Value {
@@ -87,13 +195,28 @@ impl From<String> for Value {
     }
 }
 
+impl From<Vec<Value>> for Value {
+    /// Converts a `Vec` into a `Value::Array`.
+    fn from(value: Vec<Value>) -> Self {
+        Value::Array(value)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    /// Converts a map into a `Value::Object`.
+    fn from(value: BTreeMap<String, Value>) -> Self {
+        Value::Object(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_value_type_deserialize() {
-        let input = r#"["int", "integer", "float", "bool", "boolean", "string"]"#;
+        let input =
+            r#"["int", "integer", "float", "bool", "boolean", "string", "array", "object"]"#;
         let actual: Vec<ValueType> = serde_json::from_str(input).expect("should deserialize");
         let expected = vec![
             ValueType::Integer,
@@ -102,13 +225,42 @@ mod tests {
             ValueType::Boolean,
             ValueType::Boolean,
             ValueType::String,
+            ValueType::Array,
+            ValueType::Object,
         ];
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_value_type_from_str() {
+        let tests = vec![
+            ("int", ValueType::Integer),
+            ("integer", ValueType::Integer),
+            ("float", ValueType::Float),
+            ("bool", ValueType::Boolean),
+            ("boolean", ValueType::Boolean),
+            ("string", ValueType::String),
+            ("array", ValueType::Array),
+            ("object", ValueType::Object),
+        ];
+
+        for (input, expected) in tests {
+            let actual: ValueType = input.parse().expect("value type should parse");
+            assert_eq!(actual, expected);
+            assert_eq!(actual.to_string().parse::<ValueType>().unwrap(), expected);
+        }
+
+        for input in ["", "Integer", "number", "str"] {
+            let err = input
+                .parse::<ValueType>()
+                .expect_err("value type should be invalid");
+            assert!(matches!(err, FetaError::Request(_)));
+        }
+    }
+
     #[test]
     fn test_value_deserialize() {
-        let input = r#"[1, 1.1, true, false, "abc"]"#;
+        let input = r#"[1, 1.1, true, false, "abc", ["a", "b"], {"a": 1, "b": {"c": "d"}}]"#;
         let actual: Vec<Value> = serde_json::from_str(input).expect("should deserialize");
         let expected = vec![
             Value::Integer(1),
@@ -116,6 +268,14 @@ mod tests {
             Value::Boolean(true),
             Value::Boolean(false),
             Value::String("abc".to_string()),
+            Value::Array(vec!["a".into(), "b".into()]),
+            Value::Object(BTreeMap::from([
+                ("a".to_string(), Value::Integer(1)),
+                (
+                    "b".to_string(),
+                    Value::Object(BTreeMap::from([("c".to_string(), "d".into())])),
+                ),
+            ])),
         ];
         assert_eq!(actual, expected);
     }
@@ -128,9 +288,11 @@ mod tests {
             Value::Boolean(true),
             Value::Boolean(false),
             Value::String("abc".to_string()),
+            Value::Array(vec![1.into(), 2.into()]),
+            Value::Object(BTreeMap::from([("a".to_string(), Value::Integer(1))])),
         ];
         let actual = serde_json::to_string(&input).expect("should serialize");
-        let expected = r#"[1,1.1,true,false,"abc"]"#;
+        let expected = r#"[1,1.1,true,false,"abc",[1,2],{"a":1}]"#;
         assert_eq!(actual, expected);
     }
 
@@ -141,6 +303,8 @@ mod tests {
             (ValueType::Float, "float"),
             (ValueType::Boolean, "boolean"),
             (ValueType::String, "string"),
+            (ValueType::Array, "array"),
+            (ValueType::Object, "object"),
         ];
 
         for (input, expected) in tests {
@@ -160,6 +324,10 @@ mod tests {
             (Value::Boolean(true), ValueType::String, false),
             (Value::String(String::new()), ValueType::String, true),
             (Value::String(String::new()), ValueType::Integer, false),
+            (Value::Array(Vec::new()), ValueType::Array, true),
+            (Value::Array(Vec::new()), ValueType::Object, false),
+            (Value::Object(BTreeMap::new()), ValueType::Object, true),
+            (Value::Object(BTreeMap::new()), ValueType::String, false),
         ];
 
         for (input, value_type, expected) in tests {
@@ -168,6 +336,150 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_value_has_element_type() {
+        let tests = vec![
+            (Value::Array(Vec::new()), ValueType::String, true),
+            (
+                Value::from(vec!["a".into(), "b".into()]),
+                ValueType::String,
+                true,
+            ),
+            (
+                Value::from(vec!["a".into(), 1.into()]),
+                ValueType::String,
+                false,
+            ),
+            (Value::from("a"), ValueType::String, false),
+        ];
+
+        for (input, element_type, expected) in tests {
+            let actual = input.has_element_type(&element_type);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_value_is_finite() {
+        let tests = vec![
+            (Value::Float(1.1), true),
+            (Value::Float(f64::NAN), false),
+            (Value::Float(f64::INFINITY), false),
+            (Value::Integer(1), true),
+            (Value::from(vec![1.1.into(), f64::NAN.into()]), false),
+            (
+                Value::from(BTreeMap::from([("a".to_string(), f64::NAN.into())])),
+                false,
+            ),
+            (Value::Null, true),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(input.is_finite(), expected);
+        }
+    }
+
+    #[test]
+    fn test_value_as_i64() {
+        assert_eq!(Value::Integer(1).as_i64(), Some(1));
+        assert_eq!(Value::Float(1.5).as_i64(), None);
+        assert_eq!(Value::Null.as_i64(), None);
+    }
+
+    #[test]
+    fn test_value_as_f64() {
+        assert_eq!(Value::Float(1.5).as_f64(), Some(1.5));
+        assert_eq!(Value::Integer(1).as_f64(), Some(1.0));
+        assert_eq!(Value::Boolean(true).as_f64(), None);
+    }
+
+    #[test]
+    fn test_value_as_bool() {
+        assert_eq!(Value::Boolean(true).as_bool(), Some(true));
+        assert_eq!(Value::Integer(1).as_bool(), None);
+    }
+
+    #[test]
+    fn test_value_as_str() {
+        assert_eq!(Value::from("abc").as_str(), Some("abc"));
+        assert_eq!(Value::Integer(1).as_str(), None);
+    }
+
+    #[test]
+    fn test_value_partial_cmp() {
+        let object = |v: i64| Value::from(BTreeMap::from([("a".to_string(), v.into())]));
+
+        let tests = vec![
+            (Value::Null, Value::Null, Some(Ordering::Equal)),
+            (1.into(), 2.into(), Some(Ordering::Less)),
+            (2.5.into(), 1.5.into(), Some(Ordering::Greater)),
+            (1.into(), 1.5.into(), Some(Ordering::Less)),
+            (2.into(), 1.5.into(), Some(Ordering::Greater)),
+            (1.5.into(), 2.into(), Some(Ordering::Less)),
+            (1.into(), 1.0.into(), None),
+            (f64::NAN.into(), 1.into(), None),
+            (false.into(), true.into(), Some(Ordering::Less)),
+            ("a".into(), "b".into(), Some(Ordering::Less)),
+            (
+                vec![1.into(), 2.into()].into(),
+                vec![1.into(), 3.into()].into(),
+                Some(Ordering::Less),
+            ),
+            (object(1), object(1), Some(Ordering::Equal)),
+            (object(1), object(2), None),
+            ("1".into(), 1.into(), None),
+            (true.into(), 1.into(), None),
+            (Value::Null, 0.into(), None),
+        ];
+
+        for (a, b, expected) in tests {
+            assert_eq!(a.partial_cmp(&b), expected, "{:?} {:?}", a, b);
+            assert_eq!(
+                b.partial_cmp(&a),
+                expected.map(Ordering::reverse),
+                "{:?} {:?}",
+                b,
+                a
+            );
+        }
+
+        assert!(Value::from(10) > Value::from(5.5));
+        assert!(Value::from("10").partial_cmp(&Value::from(5)).is_none());
+
+        // values are only equal when they are ==
+        let pairs: Vec<(Value, Value)> = vec![(1.into(), 1.0.into()), (1.into(), 1.into())];
+        for (a, b) in pairs {
+            assert_eq!(
+                a.partial_cmp(&b) == Some(Ordering::Equal),
+                a == b,
+                "{:?} {:?}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_compare_numeric() {
+        let tests: Vec<(Value, Value, Option<Ordering>)> = vec![
+            (1.into(), 1.0.into(), Some(Ordering::Equal)),
+            (1.5.into(), 1.into(), Some(Ordering::Greater)),
+            (1.into(), 2.into(), Some(Ordering::Less)),
+            (
+                i64::MAX.into(),
+                (i64::MAX - 1).into(),
+                Some(Ordering::Greater),
+            ),
+            (f64::NAN.into(), 1.into(), None),
+            ("1".into(), 1.into(), None),
+            (Value::Null, 0.into(), None),
+        ];
+
+        for (a, b, expected) in tests {
+            assert_eq!(a.compare_numeric(&b), expected, "{:?} {:?}", a, b);
+        }
+    }
+
     #[test]
     fn test_value_from_i64() {
         let actual = Value::from(1);
@@ -197,4 +509,17 @@ mod tests {
         let actual = Value::from("abc".to_string());
         assert_eq!(actual, Value::String("abc".to_string()));
     }
+
+    #[test]
+    fn test_value_from_vec() {
+        let actual = Value::from(vec![Value::Integer(1)]);
+        assert_eq!(actual, Value::Array(vec![Value::Integer(1)]));
+    }
+
+    #[test]
+    fn test_value_from_map() {
+        let input = BTreeMap::from([("a".to_string(), Value::Integer(1))]);
+        let actual = Value::from(input.clone());
+        assert_eq!(actual, Value::Object(input));
+    }
 }
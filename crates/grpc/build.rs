@@ -0,0 +1,12 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // a vendored protoc is used so that the crate builds without a system installation
+    let protoc = protoc_bin_vendored::protoc_bin_path()?;
+
+    // SAFETY: build scripts are single threaded, so there are no concurrent reads of the environment
+    unsafe {
+        std::env::set_var("PROTOC", protoc);
+    }
+
+    tonic_prost_build::compile_protos("proto/feta.proto")?;
+    Ok(())
+}
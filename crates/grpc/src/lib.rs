@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use arc_swap::{ArcSwap, Guard};
+use feta::{Features, FetaError, config::Config};
+use prost_types::value::Kind;
+use tonic::{Request, Response, Status};
+
+use crate::proto::{
+    DecideAllRequest, DecideAllResponse, DecideRequest, ReloadRequest, ReloadResponse,
+    feta_server::{Feta, FetaServer},
+};
+
+/// The protobuf types and the generated client and server.
+pub mod proto {
+    tonic::include_proto!("feta.v1");
+}
+
+/// A gRPC service that evaluates features using a shared `Features` registry.
+#[derive(Debug, Clone)]
+pub struct FetaService {
+    features: Arc<ArcSwap<Features>>,
+}
+
+impl FetaService {
+    /// Creates a new `FetaService` backed by the given features.
+    pub fn new(features: Features) -> Self {
+        Self {
+            features: Arc::new(ArcSwap::from_pointee(features)),
+        }
+    }
+
+    /// Wraps the service in a `FetaServer`, which can be added to a tonic server.
+    pub fn into_server(self) -> FetaServer<Self> {
+        FetaServer::new(self)
+    }
+
+    /// Loads the current features, which are unaffected by a concurrent reload.
+    fn load(&self) -> Guard<Arc<Features>> {
+        self.features.load()
+    }
+}
+
+#[tonic::async_trait]
+impl Feta for FetaService {
+    /// Evaluates a single feature.
+    async fn decide(
+        &self,
+        request: Request<DecideRequest>,
+    ) -> Result<Response<proto::Decision>, Status> {
+        let req = request.into_inner();
+        let ctx = to_context(req.context.unwrap_or_default())?;
+
+        let decision = self.load().decide(&req.feature_key, &ctx);
+        Ok(Response::new(decision.into()))
+    }
+
+    /// Evaluates all features.
+    async fn decide_all(
+        &self,
+        request: Request<DecideAllRequest>,
+    ) -> Result<Response<DecideAllResponse>, Status> {
+        let ctx = to_context(request.into_inner().context.unwrap_or_default())?;
+
+        let decisions = self
+            .load()
+            .decide_all(&ctx)
+            .into_iter()
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+
+        Ok(Response::new(DecideAllResponse { decisions }))
+    }
+
+    /// Replaces the features with those from the JSON configuration.
+    ///
+    /// The new features are built before they are swapped in, so decisions are not blocked while the configuration is compiled.
+    async fn reload(
+        &self,
+        request: Request<ReloadRequest>,
+    ) -> Result<Response<ReloadResponse>, Status> {
+        let cfg = Config::from_json(&request.into_inner().config_json).map_err(to_status)?;
+        let features = self.load().rebuild(&cfg).map_err(to_status)?;
+        self.features.store(Arc::new(features));
+
+        Ok(Response::new(ReloadResponse {}))
+    }
+}
+
+impl From<feta::Decision> for proto::Decision {
+    /// Converts a `feta::Decision` into a `proto::Decision`.
+    fn from(value: feta::Decision) -> Self {
+        proto::Decision {
+            hash: value.hash,
+            bucket: value.bucket,
+            variant: value.variant,
+            reason: proto::Reason::from(value.reason).into(),
+            value: Some(value.value.into()),
+            value_type: value
+                .value_type
+                .map_or(proto::ValueType::Unspecified, proto::ValueType::from)
+                .into(),
+            audience: value.audience,
+            error: value.error.map(|e| e.to_string()),
+            warning: value.warning.map(|e| e.to_string()),
+            anonymous: value.anonymous,
+            is_default: value.is_default,
+            rule_index: value
+                .rule_index
+                .map(|i| u32::try_from(i).unwrap_or(u32::MAX)),
+            metadata: value
+                .metadata
+                .map(|m| m.into_iter().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<feta::Value> for proto::Value {
+    /// Converts a `feta::Value` into a `proto::Value`, encoding arrays and objects as JSON strings.
+    fn from(value: feta::Value) -> Self {
+        use proto::value::Kind;
+
+        let kind = match value {
+            feta::Value::Null => Kind::Null(prost_types::NullValue::NullValue.into()),
+            feta::Value::Integer(i) => Kind::Integer(i),
+            feta::Value::Float(f) => Kind::Float(f),
+            feta::Value::Boolean(b) => Kind::Boolean(b),
+            feta::Value::String(s) => Kind::String(s),
+            // serializing arrays and maps with string keys cannot fail
+            a @ feta::Value::Array(_) => Kind::Array(serde_json::to_string(&a).unwrap()),
+            o @ feta::Value::Object(_) => Kind::Object(serde_json::to_string(&o).unwrap()),
+        };
+
+        proto::Value { kind: Some(kind) }
+    }
+}
+
+impl From<feta::Reason> for proto::Reason {
+    /// Converts a `feta::Reason` into a `proto::Reason`.
+    fn from(value: feta::Reason) -> Self {
+        match value {
+            feta::Reason::Unknown => proto::Reason::Unknown,
+            feta::Reason::Disabled => proto::Reason::Disabled,
+            feta::Reason::Static => proto::Reason::Static,
+            feta::Reason::Split => proto::Reason::Split,
+            feta::Reason::Match => proto::Reason::Match,
+            feta::Reason::MatchSplit => proto::Reason::MatchSplit,
+            feta::Reason::Override => proto::Reason::Override,
+            feta::Reason::Prerequisite => proto::Reason::Prerequisite,
+            feta::Reason::NoMatch => proto::Reason::NoMatch,
+            feta::Reason::Excluded => proto::Reason::Excluded,
+            feta::Reason::Error => proto::Reason::Error,
+        }
+    }
+}
+
+impl From<feta::ValueType> for proto::ValueType {
+    /// Converts a `feta::ValueType` into a `proto::ValueType`.
+    fn from(value: feta::ValueType) -> Self {
+        match value {
+            feta::ValueType::Integer => proto::ValueType::Integer,
+            feta::ValueType::Float => proto::ValueType::Float,
+            feta::ValueType::Boolean => proto::ValueType::Boolean,
+            feta::ValueType::String => proto::ValueType::String,
+            feta::ValueType::Array => proto::ValueType::Array,
+            feta::ValueType::Object => proto::ValueType::Object,
+        }
+    }
+}
+
+/// Converts a `proto::Context` into a `feta::Context` using the JSON context representation.
+fn to_context(ctx: proto::Context) -> Result<feta::Context, Status> {
+    let to_object =
+        |s: Option<prost_types::Struct>| s.map(|s| serde_json::Value::Object(to_json_map(s)));
+
+    let json = serde_json::json!({
+        "user_key": ctx.user_key,
+        "attributes": to_object(ctx.attributes),
+        "globals": to_object(ctx.globals),
+        "overrides": (!ctx.overrides.is_empty()).then_some(ctx.overrides),
+        "anonymous": ctx.anonymous,
+    });
+
+    serde_json::from_value(json).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+/// Converts a protobuf `Struct` into a JSON map.
+fn to_json_map(s: prost_types::Struct) -> serde_json::Map<String, serde_json::Value> {
+    s.fields.into_iter().map(|(k, v)| (k, to_json(v))).collect()
+}
+
+/// Converts a protobuf `Value` into a JSON value.
+///
+/// Protobuf numbers are always doubles, so whole numbers are converted to integers to match JSON contexts.
+fn to_json(value: prost_types::Value) -> serde_json::Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::NumberValue(n)) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+            (n as i64).into()
+        }
+        Some(Kind::NumberValue(n)) => n.into(),
+        Some(Kind::StringValue(s)) => s.into(),
+        Some(Kind::BoolValue(b)) => b.into(),
+        Some(Kind::StructValue(s)) => serde_json::Value::Object(to_json_map(s)),
+        Some(Kind::ListValue(l)) => l.values.into_iter().map(to_json).collect(),
+    }
+}
+
+/// Converts a `FetaError` into a `Status`, where configuration errors are invalid arguments.
+fn to_status(err: FetaError) -> Status {
+    match err {
+        FetaError::NotFound(_) => Status::not_found(err.to_string()),
+        FetaError::Request(_) | FetaError::Configuration(_) => {
+            Status::invalid_argument(err.to_string())
+        }
+        FetaError::Targeting(_) => Status::internal(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use feta::Object;
+
+    use super::*;
+
+    #[test]
+    fn test_to_context() {
+        let number = |n: f64| prost_types::Value {
+            kind: Some(Kind::NumberValue(n)),
+        };
+
+        let input = proto::Context {
+            user_key: "a".to_string(),
+            attributes: Some(prost_types::Struct {
+                fields: BTreeMap::from([
+                    ("age".to_string(), number(30.0)),
+                    ("score".to_string(), number(1.5)),
+                    (
+                        "beta".to_string(),
+                        prost_types::Value {
+                            kind: Some(Kind::BoolValue(true)),
+                        },
+                    ),
+                ]),
+            }),
+            ..Default::default()
+        };
+
+        let actual = to_context(input).expect("context should convert");
+        assert_eq!(actual.user_key, "a");
+        assert_eq!(actual.attribute("age"), Some(&Object::Integer(30)));
+        assert_eq!(actual.attribute("score"), Some(&Object::Float(1.5)));
+        assert_eq!(actual.attribute("beta"), Some(&Object::Boolean(true)));
+        assert!(actual.overrides.is_none());
+    }
+
+    #[test]
+    fn test_decision_from() {
+        let input = feta::DecisionBuilder::new()
+            .variant("a")
+            .value(feta::Value::from(vec![1.into()]))
+            .error(FetaError::Request("error".to_string()));
+
+        let actual = proto::Decision::from(input);
+        assert_eq!(actual.reason(), proto::Reason::Error);
+        assert_eq!(actual.value_type(), proto::ValueType::Unspecified);
+        assert_eq!(
+            actual.value.and_then(|v| v.kind),
+            Some(proto::value::Kind::Array("[1]".to_string()))
+        );
+        assert_eq!(actual.error, Some("Request error: error".to_string()));
+    }
+}
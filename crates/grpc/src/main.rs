@@ -0,0 +1,52 @@
+use std::{fs::File, io::BufReader, net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use feta::{Features, FetaError};
+use feta_grpc::FetaService;
+use tonic::transport::Server;
+
+/// Serves feta decisions over gRPC.
+#[derive(Debug, Parser)]
+#[command(name = "feta-grpc", version)]
+struct Cli {
+    /// The path to the JSON configuration file loaded at startup.
+    #[arg(long)]
+    config: PathBuf,
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:50051")]
+    addr: SocketAddr,
+}
+
+/// Loads the features from the configuration file.
+fn load_features(path: &PathBuf) -> Result<Features, FetaError> {
+    let file = File::open(path).map_err(|e| {
+        FetaError::Configuration(format!("failed to read {}: {}", path.display(), e))
+    })?;
+
+    Features::from_reader(BufReader::new(file))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let features = match load_features(&cli.config) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let server = Server::builder()
+        .add_service(FetaService::new(features).into_server())
+        .serve(cli.addr);
+
+    match server.await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
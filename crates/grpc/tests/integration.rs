@@ -0,0 +1,195 @@
+use feta::{Features, Reason, Value};
+use feta_grpc::{
+    FetaService,
+    proto::{self, DecideAllRequest, DecideRequest, ReloadRequest, feta_client::FetaClient},
+};
+use prost_types::value::Kind;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Code, transport::Channel, transport::Server};
+
+/// Starts the service on an ephemeral port and returns a connected client.
+async fn new_client() -> FetaClient<Channel> {
+    let features =
+        Features::from_json(feta_integration::CONFIG).expect("failed to create features");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(FetaService::new(features).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    FetaClient::connect(format!("http://{}", addr))
+        .await
+        .expect("failed to connect")
+}
+
+fn convert_context(ctx: &feta::Context) -> proto::Context {
+    let json = serde_json::to_value(ctx).unwrap();
+
+    let to_struct = |value: &serde_json::Value| {
+        value.as_object().map(|m| prost_types::Struct {
+            fields: m
+                .iter()
+                .map(|(k, v)| (k.clone(), convert_json(v)))
+                .collect(),
+        })
+    };
+
+    proto::Context {
+        user_key: ctx.user_key.clone(),
+        attributes: to_struct(&json["attributes"]),
+        globals: to_struct(&json["globals"]),
+        overrides: ctx
+            .overrides
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        anonymous: ctx.anonymous,
+    }
+}
+
+fn convert_json(value: &serde_json::Value) -> prost_types::Value {
+    let kind = match value {
+        serde_json::Value::Null => Kind::NullValue(0),
+        serde_json::Value::Bool(b) => Kind::BoolValue(*b),
+        serde_json::Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap()),
+        serde_json::Value::String(s) => Kind::StringValue(s.clone()),
+        serde_json::Value::Array(a) => Kind::ListValue(prost_types::ListValue {
+            values: a.iter().map(convert_json).collect(),
+        }),
+        serde_json::Value::Object(m) => Kind::StructValue(prost_types::Struct {
+            fields: m
+                .iter()
+                .map(|(k, v)| (k.clone(), convert_json(v)))
+                .collect(),
+        }),
+    };
+
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn convert_decision(decision: proto::Decision) -> feta_integration::Decision {
+    let reason = match decision.reason() {
+        proto::Reason::Unknown => Reason::Unknown,
+        proto::Reason::Disabled => Reason::Disabled,
+        proto::Reason::Static => Reason::Static,
+        proto::Reason::Split => Reason::Split,
+        proto::Reason::Match => Reason::Match,
+        proto::Reason::MatchSplit => Reason::MatchSplit,
+        proto::Reason::Override => Reason::Override,
+        proto::Reason::Prerequisite => Reason::Prerequisite,
+        proto::Reason::NoMatch => Reason::NoMatch,
+        proto::Reason::Excluded => Reason::Excluded,
+        proto::Reason::Error => Reason::Error,
+    };
+
+    feta_integration::Decision {
+        variant: decision.variant,
+        reason,
+        value: convert_value(decision.value.and_then(|v| v.kind)),
+        audience: decision.audience,
+        has_error: reason == Reason::Error,
+        is_default: decision.is_default,
+    }
+}
+
+fn convert_value(kind: Option<proto::value::Kind>) -> Value {
+    use proto::value::Kind;
+
+    match kind {
+        None | Some(Kind::Null(_)) => Value::Null,
+        Some(Kind::Integer(i)) => Value::Integer(i),
+        Some(Kind::Float(f)) => Value::Float(f),
+        Some(Kind::Boolean(b)) => Value::Boolean(b),
+        Some(Kind::String(s)) => Value::String(s),
+        Some(Kind::Array(s) | Kind::Object(s)) => {
+            serde_json::from_str(&s).expect("failed to parse value")
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_decide() {
+    let mut client = new_client().await;
+
+    for test in feta_integration::decide_cases() {
+        let req = DecideRequest {
+            feature_key: test.feature_key.clone(),
+            context: Some(convert_context(&test.context)),
+        };
+
+        let actual = client
+            .decide(req)
+            .await
+            .expect("decide failed")
+            .into_inner();
+        assert_eq!(
+            convert_decision(actual),
+            test.expected,
+            "{}",
+            test.feature_key
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_decide_all() {
+    let mut client = new_client().await;
+
+    for test in feta_integration::decide_all_cases() {
+        let req = DecideAllRequest {
+            context: Some(convert_context(&test.context)),
+        };
+
+        let actual = client
+            .decide_all(req)
+            .await
+            .expect("decide all failed")
+            .into_inner();
+        assert_eq!(actual.decisions.len(), test.expected.len());
+
+        for (key, decision) in actual.decisions {
+            assert_eq!(convert_decision(decision), test.expected[&key], "{}", key);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_reload() {
+    let mut client = new_client().await;
+
+    let err = client
+        .reload(ReloadRequest {
+            config_json: "{".to_string(),
+        })
+        .await
+        .expect_err("invalid config should fail");
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let config_json =
+        feta_integration::CONFIG.replacen(r#""enabled": true"#, r#""enabled": false"#, 1);
+    client
+        .reload(ReloadRequest { config_json })
+        .await
+        .expect("reload failed");
+
+    let req = DecideRequest {
+        feature_key: "bool_feature".to_string(),
+        context: Some(proto::Context {
+            user_key: "a".to_string(),
+            ..Default::default()
+        }),
+    };
+
+    let actual = client
+        .decide(req)
+        .await
+        .expect("decide failed")
+        .into_inner();
+    assert_eq!(actual.reason(), proto::Reason::Disabled);
+}
@@ -32,6 +32,7 @@ pub struct Decision {
     pub value: Value,
     pub audience: Option<String>,
     pub has_error: bool,
+    pub is_default: bool,
 }
 
 pub fn decide_cases() -> Vec<DecideTestCase> {
@@ -49,5 +50,6 @@ pub fn assert_decision_eq(actual: &feta::Decision, expected: &Decision) {
     assert_eq!(actual.reason, expected.reason);
     assert_eq!(actual.value, expected.value);
     assert_eq!(actual.audience, expected.audience);
-    assert_eq!(actual.error.is_some(), expected.has_error);
+    assert_eq!(actual.is_error(), expected.has_error);
+    assert_eq!(actual.is_default, expected.is_default);
 }
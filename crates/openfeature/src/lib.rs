@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use feta::{Context, ContextBuilder, Features, FetaError, Object, Reason, Value, config::Config};
+use open_feature::{
+    EvaluationContext, EvaluationContextFieldValue, EvaluationError, EvaluationErrorCode,
+    EvaluationReason, EvaluationResult, StructValue,
+    provider::{FeatureProvider, ProviderMetadata, ResolutionDetails},
+};
+use time::format_description::well_known::Rfc3339;
+
+/// An OpenFeature provider that evaluates flags using a `Features` registry.
+pub struct FetaProvider {
+    metadata: ProviderMetadata,
+    features: Features,
+}
+
+impl FetaProvider {
+    /// Creates a new `FetaProvider` backed by the given features.
+    pub fn new(features: Features) -> Self {
+        Self {
+            metadata: ProviderMetadata::new("feta"),
+            features,
+        }
+    }
+
+    /// Creates a new `FetaProvider` from the given configuration.
+    pub fn from_config(cfg: &Config) -> Result<Self, FetaError> {
+        Ok(Self::new(Features::from_config(cfg)?))
+    }
+
+    /// Evaluates the specified flag, converting the decision value with the given function.
+    fn resolve<T>(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+        convert: impl FnOnce(Value) -> Option<T>,
+    ) -> EvaluationResult<ResolutionDetails<T>> {
+        let ctx = to_context(evaluation_context)?;
+        let decision = self.features.decide(flag_key, &ctx);
+
+        if decision.is_error() {
+            return Err(match &decision.error {
+                Some(err) => to_error(err),
+                None => new_error(
+                    EvaluationErrorCode::General(decision.reason.to_string()),
+                    "",
+                ),
+            });
+        }
+
+        let value_type = decision.value_type;
+        let value = convert(decision.value).ok_or_else(|| {
+            let message = match value_type {
+                Some(t) => format!("feature {} has type {}", flag_key, t),
+                None => format!("feature {} has no value type", flag_key),
+            };
+            new_error(EvaluationErrorCode::TypeMismatch, &message)
+        })?;
+
+        Ok(ResolutionDetails {
+            value,
+            variant: Some(decision.variant),
+            reason: Some(to_reason(decision.reason)),
+            flag_metadata: None,
+        })
+    }
+}
+
+#[async_trait]
+impl FeatureProvider for FetaProvider {
+    /// Returns the metadata for the provider.
+    fn metadata(&self) -> &ProviderMetadata {
+        &self.metadata
+    }
+
+    /// Resolves a boolean flag value.
+    async fn resolve_bool_value(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+    ) -> EvaluationResult<ResolutionDetails<bool>> {
+        self.resolve(flag_key, evaluation_context, |v| v.as_bool())
+    }
+
+    /// Resolves an integer flag value.
+    async fn resolve_int_value(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+    ) -> EvaluationResult<ResolutionDetails<i64>> {
+        self.resolve(flag_key, evaluation_context, |v| v.as_i64())
+    }
+
+    /// Resolves a float flag value, which must be declared as a float rather than widened from an integer.
+    async fn resolve_float_value(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+    ) -> EvaluationResult<ResolutionDetails<f64>> {
+        self.resolve(flag_key, evaluation_context, |v| match v {
+            Value::Float(f) => Some(f),
+            _ => None,
+        })
+    }
+
+    /// Resolves a string flag value.
+    async fn resolve_string_value(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+    ) -> EvaluationResult<ResolutionDetails<String>> {
+        self.resolve(flag_key, evaluation_context, |v| match v {
+            Value::String(s) => Some(s),
+            _ => None,
+        })
+    }
+
+    /// Resolves an object flag value.
+    async fn resolve_struct_value(
+        &self,
+        flag_key: &str,
+        evaluation_context: &EvaluationContext,
+    ) -> EvaluationResult<ResolutionDetails<StructValue>> {
+        self.resolve(flag_key, evaluation_context, |v| match v {
+            Value::Object(map) => Some(to_struct_value(map)),
+            _ => None,
+        })
+    }
+}
+
+/// Converts an OpenFeature `EvaluationContext` into a `Context`, using the targeting key as the user key.
+///
+/// Timestamps are converted to RFC3339 strings, and struct fields are ignored as they cannot be inspected.
+fn to_context(evaluation_context: &EvaluationContext) -> EvaluationResult<Context> {
+    let Some(targeting_key) = &evaluation_context.targeting_key else {
+        return Err(new_error(
+            EvaluationErrorCode::TargetingKeyMissing,
+            "targeting key is required",
+        ));
+    };
+
+    let mut builder = ContextBuilder::new().user_key(targeting_key);
+    for (key, value) in &evaluation_context.custom_fields {
+        let value = match value {
+            EvaluationContextFieldValue::Bool(b) => Object::Boolean(*b),
+            EvaluationContextFieldValue::Int(i) => Object::Integer(*i),
+            EvaluationContextFieldValue::Float(f) => Object::Float(*f),
+            EvaluationContextFieldValue::String(s) => Object::String(s.clone()),
+            EvaluationContextFieldValue::DateTime(t) => match t.format(&Rfc3339) {
+                Ok(s) => Object::String(s),
+                Err(e) => {
+                    return Err(new_error(
+                        EvaluationErrorCode::InvalidContext,
+                        &e.to_string(),
+                    ));
+                }
+            },
+            EvaluationContextFieldValue::Struct(_) => continue,
+        };
+
+        builder = builder.attribute(key, value);
+    }
+
+    builder.build().map_err(|e| to_error(&e))
+}
+
+/// Converts a `Reason` into the equivalent OpenFeature reason, using the reason name where there is no equivalent.
+fn to_reason(reason: Reason) -> EvaluationReason {
+    match reason {
+        Reason::Unknown => EvaluationReason::Unknown,
+        Reason::Disabled => EvaluationReason::Disabled,
+        Reason::Static => EvaluationReason::Static,
+        Reason::Split => EvaluationReason::Split,
+        Reason::NoMatch => EvaluationReason::Default,
+        Reason::Match | Reason::MatchSplit => EvaluationReason::TargetingMatch,
+        Reason::Error => EvaluationReason::Error,
+        Reason::Override | Reason::Prerequisite | Reason::Excluded => {
+            EvaluationReason::Other(reason.to_string())
+        }
+    }
+}
+
+/// Converts a `FetaError` into an OpenFeature `EvaluationError` with the equivalent error code.
+fn to_error(err: &FetaError) -> EvaluationError {
+    let code = match err {
+        FetaError::NotFound(_) => EvaluationErrorCode::FlagNotFound,
+        FetaError::Request(_) => EvaluationErrorCode::InvalidContext,
+        FetaError::Configuration(_) => EvaluationErrorCode::General("configuration".to_string()),
+        FetaError::Targeting(_) => EvaluationErrorCode::General("targeting".to_string()),
+    };
+
+    new_error(code, &err.to_string())
+}
+
+/// Creates a new `EvaluationError` with the specified code and message.
+fn new_error(code: EvaluationErrorCode, message: &str) -> EvaluationError {
+    EvaluationError {
+        code,
+        message: Some(message.to_string()),
+    }
+}
+
+/// Converts an object `Value` into an OpenFeature `StructValue`, omitting null fields.
+fn to_struct_value(map: BTreeMap<String, Value>) -> StructValue {
+    let fields: HashMap<String, open_feature::Value> = map
+        .into_iter()
+        .filter_map(|(k, v)| to_value(v).map(|v| (k, v)))
+        .collect();
+
+    StructValue { fields }
+}
+
+/// Converts a `Value` into an OpenFeature `Value`, returning `None` for null values.
+fn to_value(value: Value) -> Option<open_feature::Value> {
+    match value {
+        Value::Null => None,
+        Value::Integer(i) => Some(open_feature::Value::Int(i)),
+        Value::Float(f) => Some(open_feature::Value::Float(f)),
+        Value::Boolean(b) => Some(open_feature::Value::Bool(b)),
+        Value::String(s) => Some(open_feature::Value::String(s)),
+        Value::Array(items) => Some(open_feature::Value::Array(
+            items.into_iter().filter_map(to_value).collect(),
+        )),
+        Value::Object(map) => Some(open_feature::Value::Struct(to_struct_value(map))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_context() {
+        let evaluation_context = EvaluationContext::default()
+            .with_targeting_key("user_key")
+            .with_custom_field("is_beta", true)
+            .with_custom_field("age", 50i64)
+            .with_custom_field("email", "user@company.com");
+
+        let actual = to_context(&evaluation_context).expect("context should convert");
+        assert_eq!(actual.user_key, "user_key");
+        assert_eq!(actual.attribute("is_beta"), Some(&Object::Boolean(true)));
+        assert_eq!(actual.attribute("age"), Some(&Object::Integer(50)));
+        assert_eq!(
+            actual.attribute("email"),
+            Some(&Object::String("user@company.com".to_string()))
+        );
+
+        let err = to_context(&EvaluationContext::default()).expect_err("key should be required");
+        assert_eq!(err.code, EvaluationErrorCode::TargetingKeyMissing);
+    }
+
+    #[test]
+    fn test_to_reason() {
+        let tests = vec![
+            (Reason::Unknown, EvaluationReason::Unknown),
+            (Reason::Disabled, EvaluationReason::Disabled),
+            (Reason::Static, EvaluationReason::Static),
+            (Reason::Split, EvaluationReason::Split),
+            (Reason::NoMatch, EvaluationReason::Default),
+            (Reason::Match, EvaluationReason::TargetingMatch),
+            (Reason::MatchSplit, EvaluationReason::TargetingMatch),
+            (
+                Reason::Override,
+                EvaluationReason::Other("override".to_string()),
+            ),
+            (
+                Reason::Prerequisite,
+                EvaluationReason::Other("prerequisite".to_string()),
+            ),
+            (
+                Reason::Excluded,
+                EvaluationReason::Other("excluded".to_string()),
+            ),
+            (Reason::Error, EvaluationReason::Error),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(to_reason(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_to_error() {
+        let tests = vec![
+            (
+                FetaError::NotFound(String::new()),
+                EvaluationErrorCode::FlagNotFound,
+            ),
+            (
+                FetaError::Request(String::new()),
+                EvaluationErrorCode::InvalidContext,
+            ),
+            (
+                FetaError::Configuration(String::new()),
+                EvaluationErrorCode::General("configuration".to_string()),
+            ),
+            (
+                FetaError::Targeting(String::new()),
+                EvaluationErrorCode::General("targeting".to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(to_error(&input).code, expected);
+        }
+    }
+
+    #[test]
+    fn test_to_value() {
+        let input = Value::from(BTreeMap::from([
+            ("a".to_string(), Value::from(vec![1.into(), Value::Null])),
+            ("b".to_string(), Value::Null),
+        ]));
+
+        let expected = open_feature::Value::Struct(StructValue {
+            fields: HashMap::from([(
+                "a".to_string(),
+                open_feature::Value::Array(vec![open_feature::Value::Int(1)]),
+            )]),
+        });
+
+        assert_eq!(to_value(input), Some(expected));
+    }
+}
@@ -0,0 +1,104 @@
+use feta::{Object, Reason, Value, config::Config};
+use feta_openfeature::FetaProvider;
+use open_feature::{EvaluationContext, EvaluationReason, provider::FeatureProvider};
+
+#[tokio::test]
+async fn test_provider() {
+    let config: Config =
+        serde_json::from_str(feta_integration::CONFIG).expect("failed to deserialize config");
+    let provider = FetaProvider::from_config(&config).expect("failed to create provider");
+
+    for test in feta_integration::decide_cases() {
+        // overrides cannot be expressed through an evaluation context
+        if test.context.overrides.is_some() {
+            continue;
+        }
+
+        let ctx = convert_context(&test.context);
+        let key = test.feature_key.as_str();
+
+        let (value, result) = match &test.expected.value {
+            Value::Boolean(_) => {
+                let result = provider.resolve_bool_value(key, &ctx).await;
+                (
+                    result.as_ref().map(|r| Value::from(r.value)).ok(),
+                    result.map(|r| (r.variant, r.reason)),
+                )
+            }
+            Value::Integer(_) => {
+                let result = provider.resolve_int_value(key, &ctx).await;
+                (
+                    result.as_ref().map(|r| Value::from(r.value)).ok(),
+                    result.map(|r| (r.variant, r.reason)),
+                )
+            }
+            Value::Float(_) => {
+                let result = provider.resolve_float_value(key, &ctx).await;
+                (
+                    result.as_ref().map(|r| Value::from(r.value)).ok(),
+                    result.map(|r| (r.variant, r.reason)),
+                )
+            }
+            Value::String(_) => {
+                let result = provider.resolve_string_value(key, &ctx).await;
+                (
+                    result.as_ref().map(|r| Value::from(r.value.clone())).ok(),
+                    result.map(|r| (r.variant, r.reason)),
+                )
+            }
+            v => panic!("unsupported value: {:?}", v),
+        };
+
+        if test.expected.has_error {
+            assert!(result.is_err(), "{}", key);
+            continue;
+        }
+
+        let (variant, reason) = result.expect("failed to resolve");
+        assert_eq!(value, Some(test.expected.value), "{}", key);
+        assert_eq!(variant, Some(test.expected.variant), "{}", key);
+        assert_eq!(
+            reason,
+            Some(convert_reason(test.expected.reason)),
+            "{}",
+            key
+        );
+    }
+
+    let ctx = EvaluationContext::default().with_targeting_key("user_key");
+    let is_err = provider.resolve_bool_value("invalid", &ctx).await.is_err();
+    assert!(is_err);
+
+    // type mismatch
+    let is_err = provider
+        .resolve_string_value("bool_feature", &ctx)
+        .await
+        .is_err();
+    assert!(is_err);
+}
+
+fn convert_context(ctx: &feta::Context) -> EvaluationContext {
+    let mut result = EvaluationContext::default().with_targeting_key(ctx.user_key.clone());
+
+    for (key, value) in ctx.attributes.iter().flatten() {
+        result = match value {
+            Object::Boolean(b) => result.with_custom_field(key.clone(), *b),
+            Object::Integer(i) => result.with_custom_field(key.clone(), *i),
+            Object::Float(f) => result.with_custom_field(key.clone(), *f),
+            Object::String(s) => result.with_custom_field(key.clone(), s.clone()),
+            v => panic!("unsupported attribute: {:?}", v),
+        };
+    }
+
+    result
+}
+
+fn convert_reason(reason: Reason) -> EvaluationReason {
+    match reason {
+        Reason::Disabled => EvaluationReason::Disabled,
+        Reason::Static => EvaluationReason::Static,
+        Reason::Split => EvaluationReason::Split,
+        Reason::Match | Reason::MatchSplit => EvaluationReason::TargetingMatch,
+        r => panic!("unexpected reason: {}", r),
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use arc_swap::{ArcSwap, Guard};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{post, put},
+};
+use feta::{Context, Decision, Features, FetaError, config::Config};
+use serde::Deserialize;
+
+/// The shared features registry, which is swapped when the configuration is replaced.
+#[derive(Clone)]
+struct AppState {
+    features: Arc<ArcSwap<Features>>,
+}
+
+impl AppState {
+    /// Loads the current features, which are unaffected by a concurrent update.
+    fn load(&self) -> Guard<Arc<Features>> {
+        self.features.load()
+    }
+}
+
+/// The request body for evaluating a single feature.
+#[derive(Debug, Deserialize)]
+pub struct DecideRequest {
+    pub feature: String,
+    pub context: Context,
+}
+
+/// The request body for evaluating all features.
+#[derive(Debug, Deserialize)]
+pub struct DecideAllRequest {
+    pub context: Context,
+}
+
+/// Creates a `Router` that serves decisions for the given features.
+///
+/// - `POST /decide` evaluates a single feature and returns the `Decision`
+/// - `POST /decide-all` evaluates all features and returns the decisions keyed by feature name
+/// - `PUT /config` replaces the features, leaving the existing features in place if the configuration is invalid
+pub fn router(features: Features) -> Router {
+    let state = AppState {
+        features: Arc::new(ArcSwap::from_pointee(features)),
+    };
+
+    Router::new()
+        .route("/decide", post(decide))
+        .route("/decide-all", post(decide_all))
+        .route("/config", put(update_config))
+        .with_state(state)
+}
+
+/// Evaluates a single feature, returning the status code for the decision error alongside the decision.
+async fn decide(State(state): State<AppState>, Json(req): Json<DecideRequest>) -> Response {
+    let decision = state.load().decide(&req.feature, &req.context);
+    (decision_status(&decision), Json(decision)).into_response()
+}
+
+/// Evaluates all features for the context.
+async fn decide_all(
+    State(state): State<AppState>,
+    Json(req): Json<DecideAllRequest>,
+) -> Json<BTreeMap<String, Decision>> {
+    Json(state.load().decide_all_sorted(&req.context))
+}
+
+/// Replaces the features with those from the configuration.
+///
+/// The new features are built before they are swapped in, so decisions are not blocked while the configuration is compiled.
+async fn update_config(
+    State(state): State<AppState>,
+    Json(cfg): Json<Config>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let features = state.load().rebuild(&cfg).map_err(|e| error_response(&e))?;
+
+    state.features.store(Arc::new(features));
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Returns the status code for the decision, where only failed decisions map to an error status.
+fn decision_status(decision: &Decision) -> StatusCode {
+    decision.error.as_ref().map_or(StatusCode::OK, error_status)
+}
+
+/// Returns the status code for the error kind.
+fn error_status(err: &FetaError) -> StatusCode {
+    match err {
+        FetaError::NotFound(_) => StatusCode::NOT_FOUND,
+        FetaError::Request(_) | FetaError::Configuration(_) => StatusCode::BAD_REQUEST,
+        FetaError::Targeting(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Creates an error response with the status code for the error kind.
+fn error_response(err: &FetaError) -> (StatusCode, String) {
+    (error_status(err), err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status() {
+        let tests = vec![
+            (FetaError::NotFound(String::new()), StatusCode::NOT_FOUND),
+            (FetaError::Request(String::new()), StatusCode::BAD_REQUEST),
+            (
+                FetaError::Configuration(String::new()),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                FetaError::Targeting(String::new()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(error_status(&input), expected);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{fs::File, io::BufReader, net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use feta::{Features, FetaError};
+
+/// Serves feta decisions over HTTP.
+#[derive(Debug, Parser)]
+#[command(name = "feta-server", version)]
+struct Cli {
+    /// The path to the JSON configuration file loaded at startup.
+    #[arg(long)]
+    config: PathBuf,
+    /// The address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+}
+
+/// Loads the features from the configuration file.
+fn load_features(path: &PathBuf) -> Result<Features, FetaError> {
+    let file = File::open(path).map_err(|e| {
+        FetaError::Configuration(format!("failed to read {}: {}", path.display(), e))
+    })?;
+
+    Features::from_reader(BufReader::new(file))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let features = match load_features(&cli.config) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let listener = match tokio::net::TcpListener::bind(cli.addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("failed to bind {}: {}", cli.addr, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match axum::serve(listener, feta_server::router(features)).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
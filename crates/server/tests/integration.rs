@@ -0,0 +1,139 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode, header},
+};
+use feta::{Features, config::Config};
+use http_body_util::BodyExt;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+/// Sends a JSON request to the router and returns the status code and response body.
+async fn send(router: &Router, method: Method, uri: &str, body: Value) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("failed to build request");
+
+    let res = router.clone().oneshot(req).await.expect("request failed");
+    let status = res.status();
+
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+    (status, body)
+}
+
+fn new_features() -> Features {
+    Features::from_json(feta_integration::CONFIG).expect("failed to create features")
+}
+
+#[tokio::test]
+async fn test_decide() {
+    let features = new_features();
+    let router = feta_server::router(new_features());
+
+    for test in feta_integration::decide_cases() {
+        // anonymous contexts are bucketed with a random key, so the hash differs between requests
+        if test.context.anonymous {
+            continue;
+        }
+
+        let body = json!({ "feature": test.feature_key, "context": test.context });
+        let (status, actual) = send(&router, Method::POST, "/decide", body).await;
+
+        let expected = features.decide(&test.feature_key, &test.context);
+        assert_eq!(
+            status.is_success(),
+            expected.is_success(),
+            "{}",
+            test.feature_key
+        );
+        assert_eq!(actual, serde_json::to_value(expected).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_decide_errors() {
+    let router = feta_server::router(new_features());
+
+    let body = json!({ "feature": "invalid", "context": { "user_key": "a" } });
+    let (status, actual) = send(&router, Method::POST, "/decide", body).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(actual["reason"], "error");
+
+    // the current time is only parsed for features with scheduled rules
+    let body = json!({ "feature": "bool_feature", "context": { "user_key": "a", "attributes": { "_now": 1 } } });
+    let (status, actual) = send(&router, Method::POST, "/decide", body).await;
+    assert_eq!(status, StatusCode::OK, "{}", actual);
+
+    let router = feta_server::router(
+        Features::from_json(
+            r#"{
+                "features": {
+                    "scheduled": {
+                        "enabled": true,
+                        "value_type": "boolean",
+                        "default_variant": "off",
+                        "audience_rules": [{ "name": "launch", "expression": "true", "variant": "on", "starts_at": "2025-01-01T00:00:00Z" }],
+                        "default_rule": { "variant": "off" }
+                    }
+                }
+            }"#,
+        )
+        .expect("failed to create features"),
+    );
+
+    let body = json!({ "feature": "scheduled", "context": { "user_key": "a", "attributes": { "_now": 1 } } });
+    let (status, actual) = send(&router, Method::POST, "/decide", body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", actual);
+}
+
+#[tokio::test]
+async fn test_decide_all() {
+    let features = new_features();
+    let router = feta_server::router(new_features());
+
+    for test in feta_integration::decide_all_cases() {
+        if test.context.anonymous {
+            continue;
+        }
+
+        let body = json!({ "context": test.context });
+        let (status, actual) = send(&router, Method::POST, "/decide-all", body).await;
+
+        let expected = features.decide_all_sorted(&test.context);
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(actual, serde_json::to_value(expected).unwrap());
+    }
+}
+
+#[tokio::test]
+async fn test_update_config() {
+    let router = feta_server::router(new_features());
+
+    let mut config: Config = serde_json::from_str(feta_integration::CONFIG).unwrap();
+    config.features.get_mut("bool_feature").unwrap().enabled = false;
+
+    let invalid = json!({ "features": { "f1": { "enabled": true } } });
+    let (status, _) = send(&router, Method::PUT, "/config", invalid).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let mut unbuildable = serde_json::to_value(&config).unwrap();
+    unbuildable["features"]["bool_feature"]["default_variant"] = json!("invalid");
+    let (status, _) = send(&router, Method::PUT, "/config", unbuildable).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let body = json!({ "feature": "bool_feature", "context": { "user_key": "a" } });
+    let (_, actual) = send(&router, Method::POST, "/decide", body.clone()).await;
+    assert_eq!(actual["reason"], "static");
+
+    let updated = serde_json::to_value(&config).unwrap();
+    let (status, _) = send(&router, Method::PUT, "/config", updated).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_, actual) = send(&router, Method::POST, "/decide", body).await;
+    assert_eq!(actual["reason"], "disabled");
+}
@@ -0,0 +1,48 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use feta_core::config::Config;
+
+/// Creates a `Config` instance with the specified number of features, each with an audience rule and a default split.
+fn new_config(count: usize) -> Config {
+    let features: Vec<String> = (0..count)
+        .map(|i| {
+            format!(
+                r#""f{i}": {{
+                    "enabled": true,
+                    "value_type": "integer",
+                    "variants": {{ "a": 1, "b": 2 }},
+                    "default_variant": "a",
+                    "audience_rules": [
+                        {{ "name": "beta", "expression": "is_beta", "variant": "b" }}
+                    ],
+                    "default_rule": {{ "distribution": {{ "a": 50, "b": 50 }} }}
+                }}"#
+            )
+        })
+        .collect();
+
+    let json = format!(r#"{{"features": {{ {} }}}}"#, features.join(","));
+    serde_json::from_str(&json).expect("config should deserialize")
+}
+
+fn bench_init(c: &mut Criterion) {
+    let mut group = c.benchmark_group("init");
+
+    for count in [10, 100, 500] {
+        let config = new_config(count);
+        let json = serde_json::to_string(&config).expect("config should serialize");
+        let binary = rmp_serde::to_vec_named(&config).expect("config should serialize");
+
+        group.bench_with_input(BenchmarkId::new("json", count), &json, |b, j| {
+            b.iter(|| serde_json::from_str::<Config>(j).expect("config should deserialize"))
+        });
+
+        group.bench_with_input(BenchmarkId::new("msgpack", count), &binary, |b, m| {
+            b.iter(|| rmp_serde::from_slice::<Config>(m).expect("config should deserialize"))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_init);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use std::sync::{OnceLock, RwLock};
+use std::sync::{OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use feta_core::Features;
 
@@ -13,6 +13,17 @@ fn get_registry() -> &'static RwLock<Features> {
     FEATURES.get_or_init(|| RwLock::new(Features::default()))
 }
 
+/// Acquires a read guard on the global registry.
+/// A poisoned lock is recovered, as the registry is only ever replaced wholesale and so remains valid.
+fn read_registry() -> RwLockReadGuard<'static, Features> {
+    get_registry().read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Acquires a write guard on the global registry, recovering from a poisoned lock.
+fn write_registry() -> RwLockWriteGuard<'static, Features> {
+    get_registry().write().unwrap_or_else(|e| e.into_inner())
+}
+
 pub mod bindings {
     use feta_core::{Context, DecisionBuilder, Features, FetaError, config::Config};
 
@@ -31,43 +42,34 @@ pub mod bindings {
         /// Initializes the global registry with the given configuration JSON.
         fn init(config_json: String) -> Result<(), String> {
             let config: Config = serde_json::from_str(&config_json).map_err(|e| e.to_string())?;
-            let features = Features::from_config(&config).map_err(|e| e.to_string())?;
-
-            let registry = super::get_registry();
-            let mut write_guard = registry.write().map_err(|e| e.to_string())?;
-
-            *write_guard = features;
+            init_config(&config)
+        }
 
-            Ok(())
+        /// Initializes the global registry with the given MessagePack configuration, which must be encoded with named fields.
+        fn init_binary(config: Vec<u8>) -> Result<(), String> {
+            let config: Config = rmp_serde::from_slice(&config).map_err(|e| e.to_string())?;
+            init_config(&config)
         }
 
         /// Evaluates the specified feature for the given context JSON and returns a `Decision` with the result.
         fn decide(feature_key: String, ctx_json: String) -> Decision {
-            let ctx: Context = match serde_json::from_str(&ctx_json) {
-                Ok(e) => e,
-                Err(e) => {
-                    return DecisionBuilder::new()
-                        .error(FetaError::Request(e.to_string()))
-                        .into();
+            // invalid contexts are still tracked, with an empty user key, so that failed evaluations are recorded
+            let (ctx, decision) = match serde_json::from_str::<Context>(&ctx_json) {
+                Ok(ctx) => {
+                    let decision = super::read_registry().decide(&feature_key, &ctx);
+                    (ctx, decision)
                 }
-            };
-
-            let read_guard = match super::get_registry().read() {
-                Ok(g) => g,
                 Err(e) => {
-                    return DecisionBuilder::new()
-                        .error(FetaError::Request(e.to_string()))
-                        .into();
+                    let decision = DecisionBuilder::new().error(FetaError::Request(e.to_string()));
+                    (Context::new(String::new()), decision)
                 }
             };
 
-            let decision = read_guard.decide(&feature_key, &ctx);
-
             #[cfg(not(test))]
             {
                 use crate::{bindings::feta::wasi::tracking::track_event, tracking::Event};
 
-                let event = Event::new(feature_key, ctx.user_key, &decision);
+                let event = Event::new(feature_key, &ctx, &decision);
                 track_event(&event);
             }
 
@@ -77,18 +79,19 @@ pub mod bindings {
         /// Evaluates all features for the given context JSON and returns a list of feature names and their corresponding `Decision` results.
         fn decide_all(ctx_json: String) -> Result<Vec<(String, Decision)>, String> {
             let ctx: Context = serde_json::from_str(&ctx_json).map_err(|e| e.to_string())?;
-            let read_guard = super::get_registry().read().map_err(|e| e.to_string())?;
+            let read_guard = super::read_registry();
 
             let decisions = read_guard.decide_all(&ctx);
 
             #[cfg(not(test))]
             {
-                use crate::{bindings::feta::wasi::tracking::track_event, tracking::Event};
+                use crate::{bindings::feta::wasi::tracking::track_events, tracking::Event};
 
-                for (feature_key, decision) in decisions.iter() {
-                    let event = Event::new(feature_key, &ctx.user_key, decision);
-                    track_event(&event);
-                }
+                let events: Vec<Event> = decisions
+                    .iter()
+                    .map(|(feature_key, decision)| Event::new(feature_key, &ctx, decision))
+                    .collect();
+                track_events(&events);
             }
 
             let result: Vec<(String, Decision)> = decisions
@@ -98,6 +101,73 @@ pub mod bindings {
 
             Ok(result)
         }
+
+        /// Evaluates the specified features for the given context JSON and returns a list of feature names and their corresponding `Decision` results, in the requested order.
+        fn decide_many(
+            feature_keys: Vec<String>,
+            ctx_json: String,
+        ) -> Result<Vec<(String, Decision)>, String> {
+            let ctx: Context = serde_json::from_str(&ctx_json).map_err(|e| e.to_string())?;
+            let read_guard = super::read_registry();
+
+            let env = ctx.environment();
+            let decisions: Vec<(String, feta_core::Decision)> = feature_keys
+                .into_iter()
+                .map(|key| {
+                    let decision = read_guard.decide_with_env(&key, &ctx, &env);
+                    (key, decision)
+                })
+                .collect();
+
+            #[cfg(not(test))]
+            {
+                use crate::{bindings::feta::wasi::tracking::track_events, tracking::Event};
+
+                let events: Vec<Event> = decisions
+                    .iter()
+                    .map(|(feature_key, decision)| Event::new(feature_key, &ctx, decision))
+                    .collect();
+                track_events(&events);
+            }
+
+            let result: Vec<(String, Decision)> = decisions
+                .into_iter()
+                .map(|(key, decision)| (key, decision.into()))
+                .collect();
+
+            Ok(result)
+        }
+
+        /// Returns the names of all configured features, sorted alphabetically.
+        fn feature_names() -> Result<Vec<String>, String> {
+            let read_guard = super::read_registry();
+
+            let mut names: Vec<String> = read_guard.feature_names().map(String::from).collect();
+            names.sort();
+
+            Ok(names)
+        }
+
+        /// Returns the active configuration as JSON, reconstructed from the registry.
+        fn get_config() -> Result<String, String> {
+            let read_guard = super::read_registry();
+            serde_json::to_string(&read_guard.to_config()).map_err(|e| e.to_string())
+        }
+    }
+
+    /// Replaces the features in the global registry with those from the given configuration.
+    ///
+    /// A configuration with no features is rejected, as it usually means the wrong file was loaded.
+    fn init_config(config: &Config) -> Result<(), String> {
+        let features = Features::from_config(config).map_err(|e| e.to_string())?;
+        if features.is_empty() {
+            return Err("config has no features".to_string());
+        }
+
+        let mut write_guard = super::write_registry();
+        *write_guard = features;
+
+        Ok(())
     }
 
     export!(Component);
@@ -122,6 +192,18 @@ mod tests {
         let is_err = Component::init("{".to_string()).is_err();
         assert!(is_err);
 
+        let is_err = Component::init(r#"{"features": {}}"#.to_string()).is_err();
+        assert!(is_err);
+
+        // test binary init with the same config
+        let config: feta_core::config::Config =
+            serde_json::from_str(feta_integration::CONFIG).expect("failed to parse config");
+        let config_binary = rmp_serde::to_vec_named(&config).expect("failed to encode config");
+        Component::init_binary(config_binary).expect("failed to initialize from binary");
+
+        let is_err = Component::init_binary(vec![0xc1]).is_err();
+        assert!(is_err);
+
         // decide cases
         for test in feta_integration::decide_cases() {
             let context_json =
@@ -146,15 +228,92 @@ mod tests {
 
             assert_eq!(actual, test.expected)
         }
+
+        // decide_many cases
+        for test in feta_integration::decide_all_cases() {
+            let context_json =
+                serde_json::to_string(&test.context).expect("failed to serialize context");
+
+            let mut feature_keys: Vec<String> = test.expected.keys().cloned().collect();
+            feature_keys.push("invalid".to_string());
+
+            let decisions = Component::decide_many(feature_keys.clone(), context_json)
+                .expect("failed to invoke decide_many");
+
+            let actual_keys: Vec<String> = decisions.iter().map(|(k, _)| k.clone()).collect();
+            assert_eq!(actual_keys, feature_keys);
+
+            let mut actual: HashMap<String, feta_integration::Decision> = decisions
+                .into_iter()
+                .map(|(k, v)| (k, convert_decision(v)))
+                .collect();
+
+            let invalid = actual.remove("invalid").expect("invalid decision missing");
+            assert_eq!(invalid.reason, feta_core::Reason::Error);
+            assert!(invalid.has_error);
+            assert_eq!(actual, test.expected)
+        }
+
+        // feature names
+        let config: feta_core::config::Config =
+            serde_json::from_str(feta_integration::CONFIG).expect("failed to parse config");
+
+        let actual = Component::feature_names().expect("failed to invoke feature_names");
+        let expected: Vec<String> = config.features.keys().cloned().collect();
+        assert_eq!(actual, expected);
+
+        // get config
+        let config_json = Component::get_config().expect("failed to invoke get_config");
+        let actual: feta_core::config::Config =
+            serde_json::from_str(&config_json).expect("failed to parse config");
+        assert_eq!(actual, config);
+    }
+
+    #[test]
+    fn test_poisoned_registry() {
+        Component::init(feta_integration::CONFIG.to_string()).expect("failed to initialize");
+
+        let result = std::thread::spawn(|| {
+            let _write_guard = write_registry();
+            panic!("poison the registry");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert!(get_registry().is_poisoned());
+
+        for test in feta_integration::decide_cases() {
+            let context_json =
+                serde_json::to_string(&test.context).expect("failed to serialize context");
+
+            let actual = Component::decide(test.feature_key, context_json);
+            assert_eq!(convert_decision(actual), test.expected)
+        }
+
+        Component::init(feta_integration::CONFIG.to_string()).expect("failed to re-initialize");
     }
 
     fn convert_decision(decision: bindings::Decision) -> feta_integration::Decision {
         feta_integration::Decision {
             variant: decision.variant,
             reason: decision.reason,
-            value: decision.value,
+            value: convert_value(decision.value),
             audience: decision.audience,
             has_error: decision.error.is_some(),
+            is_default: decision.is_default,
+        }
+    }
+
+    fn convert_value(value: types::Value) -> feta_core::Value {
+        match value {
+            types::Value::Null => feta_core::Value::Null,
+            types::Value::Integer(i) => feta_core::Value::Integer(i),
+            types::Value::Float(f) => feta_core::Value::Float(f),
+            types::Value::Boolean(b) => feta_core::Value::Boolean(b),
+            types::Value::String(s) => feta_core::Value::String(s),
+            types::Value::Array(s) | types::Value::Object(s) => {
+                serde_json::from_str(&s).expect("failed to parse value")
+            }
         }
     }
 }
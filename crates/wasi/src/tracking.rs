@@ -1,4 +1,6 @@
-use feta_core::{Reason, Value};
+use feta_core::{Context, NOW_ATTRIBUTE, Object, Reason};
+
+use crate::types::Value;
 
 /// The tracking event generated from a feature evaluation, containing details about the feature, user, and decision.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,28 +11,46 @@ pub struct Event {
     pub reason: Reason,
     pub value: Value,
     pub audience: Option<String>,
+    pub anonymous: bool,
+    /// Whether the decision was made by the default rule, which distinguishes fall-through from a matching audience.
+    pub is_default: bool,
+    /// Whether the decision has an error, so that failed evaluations with an empty variant and null value can be recorded.
+    pub has_error: bool,
+    /// The timestamp supplied by the host in the `_now` context attribute, as the component cannot read the clock.
+    pub timestamp: Option<String>,
 }
 
 impl Event {
-    /// Creates a new `Event` from the given feature key, user key, and decision.
+    /// Creates a new `Event` from the given feature key, context, and decision.
     pub(super) fn new(
         feature_key: impl Into<String>,
-        user_key: impl Into<String>,
+        ctx: &Context,
         decision: &feta_core::Decision,
     ) -> Self {
+        let timestamp = match ctx.attribute(NOW_ATTRIBUTE) {
+            Some(Object::String(s)) => Some(s.clone()),
+            _ => None,
+        };
+
         Self {
             feature_key: feature_key.into(),
-            user_key: user_key.into(),
+            user_key: ctx.user_key.clone(),
             variant: decision.variant.clone(),
             reason: decision.reason,
-            value: decision.value.clone(),
+            value: decision.value.clone().into(),
             audience: decision.audience.clone(),
+            anonymous: decision.anonymous,
+            is_default: decision.is_default,
+            has_error: decision.error.is_some(),
+            timestamp,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
 
     #[test]
@@ -41,15 +61,47 @@ mod tests {
             .audience("audience")
             .success(feta_core::Reason::Match);
 
-        let actual = Event::new("feature", "user", &decision);
+        let mut ctx = Context::new("user");
+        ctx.attributes = Some(HashMap::from([(
+            NOW_ATTRIBUTE.to_string(),
+            Object::String("2025-01-01T00:00:00Z".to_string()),
+        )]));
+
+        let actual = Event::new("feature", &ctx, &decision);
 
         let expected = Event {
             feature_key: "feature".to_string(),
             user_key: "user".to_string(),
             variant: "variant".to_string(),
             reason: feta_core::Reason::Match,
-            value: 1.into(),
+            value: Value::Integer(1),
             audience: Some("audience".to_string()),
+            anonymous: false,
+            is_default: false,
+            has_error: false,
+            timestamp: Some("2025-01-01T00:00:00Z".to_string()),
+        };
+
+        assert_eq!(actual, expected);
+
+        let actual = Event::new("feature", &Context::new("user"), &decision);
+        let expected = Event {
+            timestamp: None,
+            ..expected
+        };
+
+        assert_eq!(actual, expected);
+
+        let decision = feta_core::DecisionBuilder::new()
+            .error(feta_core::FetaError::NotFound("feature".to_string()));
+        let actual = Event::new("feature", &Context::new("user"), &decision);
+        let expected = Event {
+            variant: String::new(),
+            reason: feta_core::Reason::Error,
+            value: Value::Null,
+            audience: None,
+            has_error: true,
+            ..expected
         };
 
         assert_eq!(actual, expected);
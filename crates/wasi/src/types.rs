@@ -1,14 +1,48 @@
-pub use feta_core::{Reason, Value};
+pub use feta_core::{Reason, ValueType};
+
+/// The value of a feature variant, with array and object values encoded as JSON strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Array(String),
+    Object(String),
+}
+
+impl From<feta_core::Value> for Value {
+    /// Converts a `feta::Value` into a `Value`.
+    fn from(value: feta_core::Value) -> Self {
+        match value {
+            feta_core::Value::Null => Value::Null,
+            feta_core::Value::Integer(i) => Value::Integer(i),
+            feta_core::Value::Float(f) => Value::Float(f),
+            feta_core::Value::Boolean(b) => Value::Boolean(b),
+            feta_core::Value::String(s) => Value::String(s),
+            // serializing arrays and maps with string keys cannot fail
+            a @ feta_core::Value::Array(_) => Value::Array(serde_json::to_string(&a).unwrap()),
+            o @ feta_core::Value::Object(_) => Value::Object(serde_json::to_string(&o).unwrap()),
+        }
+    }
+}
 
 /// The decision made for a feature evaluation.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Decision {
     pub hash: u32,
+    pub bucket: u32,
     pub variant: String,
     pub reason: Reason,
     pub value: Value,
+    pub value_type: Option<ValueType>,
     pub audience: Option<String>,
     pub error: Option<String>,
+    pub anonymous: bool,
+    pub is_default: bool,
+    pub rule_index: Option<u32>,
+    pub metadata: Option<Vec<(String, String)>>,
 }
 
 impl From<feta_core::Decision> for Decision {
@@ -16,17 +50,28 @@ impl From<feta_core::Decision> for Decision {
     fn from(value: feta_core::Decision) -> Self {
         Decision {
             hash: value.hash,
+            bucket: value.bucket,
             variant: value.variant,
             reason: value.reason,
-            value: value.value,
+            value: value.value.into(),
+            value_type: value.value_type,
             audience: value.audience,
             error: value.error.map(|e| e.to_string()),
+            anonymous: value.anonymous,
+            is_default: value.is_default,
+            // features cannot have more than u32::MAX rules in practice, so the index is saturated
+            rule_index: value
+                .rule_index
+                .map(|i| u32::try_from(i).unwrap_or(u32::MAX)),
+            metadata: value.metadata.map(|m| m.into_iter().collect()),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use feta_core::FetaError;
 
     use super::*;
@@ -37,23 +82,60 @@ mod tests {
 
         let input = feta_core::Decision {
             hash: 1,
+            bucket: 100,
             variant: "variant".to_string(),
             reason: Reason::Match,
             value: 2.into(),
+            value_type: Some(ValueType::Integer),
             audience: Some("audience".to_string()),
             error: Some(err.clone()),
+            warning: None,
+            anonymous: true,
+            is_default: false,
+            rule_index: Some(1),
+            metadata: Some(BTreeMap::from([("id".to_string(), "1".to_string())])),
         };
 
         let expected = Decision {
             hash: 1,
+            bucket: 100,
             variant: "variant".to_string(),
             reason: Reason::Match,
-            value: 2.into(),
+            value: Value::Integer(2),
+            value_type: Some(ValueType::Integer),
             audience: Some("audience".to_string()),
             error: Some(err.to_string()),
+            anonymous: true,
+            is_default: false,
+            rule_index: Some(1),
+            metadata: Some(vec![("id".to_string(), "1".to_string())]),
         };
 
         let actual: Decision = input.into();
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_value_from() {
+        let tests = vec![
+            (feta_core::Value::Null, Value::Null),
+            (1.into(), Value::Integer(1)),
+            (1.5.into(), Value::Float(1.5)),
+            (true.into(), Value::Boolean(true)),
+            ("abc".into(), Value::String("abc".to_string())),
+            (
+                vec![1.into(), 2.into()].into(),
+                Value::Array("[1,2]".to_string()),
+            ),
+            (
+                BTreeMap::from([("a".to_string(), 1.into())]).into(),
+                Value::Object(r#"{"a":1}"#.to_string()),
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let actual: Value = input.into();
+            assert_eq!(actual, expected);
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, env, path::PathBuf};
 
-use feta_core::config::Config as FetaConfig;
+use feta_core::{NOW_ATTRIBUTE, Object, config::Config as FetaConfig};
 use wasmtime::{
     Config, Engine, Store,
     component::{Component, HasSelf, Linker},
@@ -9,6 +9,8 @@ use wasmtime_wasi::{ResourceTable, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiVie
 
 use crate::host_bindings::{Decision, FetaWasi, Reason, Value, feta::wasi::tracking::Event};
 
+const TIMESTAMP: &str = "2025-01-01T00:00:00Z";
+
 pub mod host_bindings {
     wasmtime::component::bindgen!({
         path: "wit/feta-wasi.wit",
@@ -20,12 +22,17 @@ struct State {
     ctx: WasiCtx,
     table: ResourceTable,
     pub captured_events: Vec<Event>,
+    pub captured_batches: Vec<Vec<Event>>,
 }
 
 impl host_bindings::feta::wasi::tracking::Host for State {
     fn track_event(&mut self, event: Event) -> () {
         self.captured_events.push(event);
     }
+
+    fn track_events(&mut self, events: Vec<Event>) -> () {
+        self.captured_batches.push(events);
+    }
 }
 
 impl WasiView for State {
@@ -50,8 +57,14 @@ fn test_instance() {
     let num_decide_cases = decide_cases.len();
 
     for test in decide_cases {
-        let context_json =
-            serde_json::to_string(&test.context).expect("failed to serialize context");
+        // the host supplies the event timestamp, as the component cannot read the clock
+        let mut context = test.context;
+        context.globals = Some(HashMap::from([(
+            NOW_ATTRIBUTE.to_string(),
+            Object::String(TIMESTAMP.to_string()),
+        )]));
+
+        let context_json = serde_json::to_string(&context).expect("failed to serialize context");
 
         let actual = instance
             .call_decide(&mut store, &test.feature_key, &context_json)
@@ -80,10 +93,123 @@ fn test_instance() {
         assert_eq!(actual, test.expected)
     }
 
-    let num_expected_events = num_decide_cases + num_decide_all_cases * config.features.len();
+    // decide_many cases
+    for test in feta_integration::decide_all_cases() {
+        let context_json =
+            serde_json::to_string(&test.context).expect("failed to serialize context");
+
+        let mut feature_keys: Vec<String> = test.expected.keys().cloned().collect();
+        feature_keys.push("invalid".to_string());
+
+        let decisions = instance
+            .call_decide_many(&mut store, &feature_keys, &context_json)
+            .expect("failed to call decide_many")
+            .expect("decide_many failed");
+
+        let mut actual: HashMap<String, feta_integration::Decision> = decisions
+            .into_iter()
+            .map(|(k, v)| (k, convert_decision(v)))
+            .collect();
+
+        let invalid = actual.remove("invalid").expect("invalid decision missing");
+        assert_eq!(invalid.reason, feta_core::Reason::Error);
+        assert!(invalid.has_error);
+        assert_eq!(actual, test.expected)
+    }
+
+    // feature names
+    let actual = instance
+        .call_feature_names(&mut store)
+        .expect("failed to call feature_names")
+        .expect("feature_names failed");
+
+    let expected: Vec<String> = config.features.keys().cloned().collect();
+    assert_eq!(actual, expected);
+
+    // get config
+    let config_json = instance
+        .call_get_config(&mut store)
+        .expect("failed to call get_config")
+        .expect("get_config failed");
+
+    let actual: FetaConfig = serde_json::from_str(&config_json).expect("failed to parse config");
+    assert_eq!(actual, config);
 
     let final_state = store.data();
-    assert_eq!(final_state.captured_events.len(), num_expected_events);
+    assert_eq!(final_state.captured_events.len(), num_decide_cases);
+    assert!(
+        final_state
+            .captured_events
+            .iter()
+            .all(|e| e.timestamp.as_deref() == Some(TIMESTAMP))
+    );
+
+    // decide_all and decide_many each track a single batch, and decide_many also tracks the invalid key
+    let expected_batch_lens: Vec<usize> = (0..num_decide_all_cases)
+        .map(|_| config.features.len())
+        .chain((0..num_decide_all_cases).map(|_| config.features.len() + 1))
+        .collect();
+
+    let actual_batch_lens: Vec<usize> = final_state
+        .captured_batches
+        .iter()
+        .map(|b| b.len())
+        .collect();
+    assert_eq!(actual_batch_lens, expected_batch_lens);
+}
+
+#[test]
+fn test_instance_binary() {
+    let config_json = feta_integration::CONFIG.to_string();
+    let config: FetaConfig =
+        serde_json::from_str(&config_json).expect("failed to deserialize config");
+
+    let (instance, mut store) = new_instance(&config_json);
+
+    let config_binary = rmp_serde::to_vec_named(&config).expect("failed to encode config");
+    instance
+        .call_init_binary(&mut store, &config_binary)
+        .expect("failed to call init_binary")
+        .expect("init_binary failed");
+
+    let config_json = instance
+        .call_get_config(&mut store)
+        .expect("failed to call get_config")
+        .expect("get_config failed");
+
+    let actual: FetaConfig = serde_json::from_str(&config_json).expect("failed to parse config");
+    assert_eq!(actual, config);
+
+    let is_err = instance
+        .call_init_binary(&mut store, &[0xc1])
+        .expect("failed to call init_binary")
+        .is_err();
+    assert!(is_err);
+}
+
+#[test]
+fn test_instance_error_event() {
+    let (instance, mut store) = new_instance(feta_integration::CONFIG);
+
+    for (feature_key, context_json) in [("invalid", r#"{"user_key": "a"}"#), ("bool_feature", "{")]
+    {
+        let actual = instance
+            .call_decide(&mut store, feature_key, context_json)
+            .expect("failed to call decide");
+        assert_eq!(actual.reason, Reason::Error);
+    }
+
+    // failed evaluations are tracked with an empty variant and null value rather than dropped
+    let events = &store.data().captured_events;
+    assert_eq!(events.len(), 2);
+    for event in events {
+        assert_eq!(event.reason, Reason::Error);
+        assert!(event.has_error);
+        assert_eq!(event.variant, "");
+        assert_eq!(event.value, Value::Null);
+    }
+    assert_eq!(events[0].user_key, "a");
+    assert_eq!(events[1].feature_key, "bool_feature");
 }
 
 fn new_instance(config_json: &str) -> (FetaWasi, Store<State>) {
@@ -106,6 +232,7 @@ fn new_instance(config_json: &str) -> (FetaWasi, Store<State>) {
         ctx: wasi,
         table: ResourceTable::new(),
         captured_events: Vec::new(),
+        captured_batches: Vec::new(),
     };
 
     let mut store = Store::new(&engine, state);
@@ -140,6 +267,7 @@ fn convert_decision(decision: Decision) -> feta_integration::Decision {
         value: convert_value(decision.value),
         audience: decision.audience,
         has_error: decision.error.is_some(),
+        is_default: decision.is_default,
     }
 }
 
@@ -150,6 +278,10 @@ fn convert_reason(reason: Reason) -> feta_core::Reason {
         Reason::Split => feta_core::Reason::Split,
         Reason::Match => feta_core::Reason::Match,
         Reason::MatchSplit => feta_core::Reason::MatchSplit,
+        Reason::Override => feta_core::Reason::Override,
+        Reason::Prerequisite => feta_core::Reason::Prerequisite,
+        Reason::NoMatch => feta_core::Reason::NoMatch,
+        Reason::Excluded => feta_core::Reason::Excluded,
         Reason::Disabled => feta_core::Reason::Disabled,
         Reason::Error => feta_core::Reason::Error,
     }
@@ -162,5 +294,8 @@ fn convert_value(value: Value) -> feta_core::Value {
         Value::Float(f) => feta_core::Value::Float(f),
         Value::Boolean(b) => feta_core::Value::Boolean(b),
         Value::String(s) => feta_core::Value::String(s),
+        Value::Array(s) | Value::Object(s) => {
+            serde_json::from_str(&s).expect("failed to parse value")
+        }
     }
 }